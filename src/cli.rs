@@ -6,10 +6,12 @@ pub use output::*;
 
 use crate::{
     folders::FolderGraphError, utils, ResourceManipulationError, SerializedData,
-    SerializedDataError,
+    SerializedDataError, SpriteExportFormat,
 };
-use crate::{Resource, YyResource, YypBoss};
+use crate::{Resource, ShaderFile, YyResource, YypBoss};
 use camino::{Utf8Path, Utf8PathBuf};
+use regex::Regex;
+use std::path::PathBuf;
 use yy_typings::{
     AnimationCurve, Extension, Font, Note, Path, Room, Script, Sequence, Shader, Sound, Sprite,
     TileSet, Timeline, TrailingCommaUtility, {EventType, Object},
@@ -61,37 +63,51 @@ pub fn parse_command(
             ResourceCommandType::Rename {
                 identifier,
                 new_name,
+                rewrite_usages,
             } => {
-                let output = match resource_command.resource {
-                    Resource::Sprite => yyp_boss.rename_resource::<Sprite>(&identifier, new_name),
-                    Resource::Script => yyp_boss.rename_resource::<Script>(&identifier, new_name),
-                    Resource::Object => yyp_boss.rename_resource::<Object>(&identifier, new_name),
-                    Resource::Note => yyp_boss.rename_resource::<Note>(&identifier, new_name),
-                    Resource::Shader => yyp_boss.rename_resource::<Shader>(&identifier, new_name),
+                match resource_command.resource {
+                    Resource::Sprite => {
+                        rename::<Sprite>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Script => {
+                        rename::<Script>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Object => {
+                        rename::<Object>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Note => {
+                        rename::<Note>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Shader => {
+                        rename::<Shader>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
                     Resource::AnimationCurve => {
-                        yyp_boss.rename_resource::<AnimationCurve>(&identifier, new_name)
+                        rename::<AnimationCurve>(yyp_boss, &identifier, new_name, rewrite_usages)
                     }
                     Resource::Extension => {
-                        yyp_boss.rename_resource::<Extension>(&identifier, new_name)
+                        rename::<Extension>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Font => {
+                        rename::<Font>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Path => {
+                        rename::<Path>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Room => {
+                        rename::<Room>(yyp_boss, &identifier, new_name, rewrite_usages)
                     }
-                    Resource::Font => yyp_boss.rename_resource::<Font>(&identifier, new_name),
-                    Resource::Path => yyp_boss.rename_resource::<Path>(&identifier, new_name),
-                    Resource::Room => yyp_boss.rename_resource::<Room>(&identifier, new_name),
                     Resource::Sequence => {
-                        yyp_boss.rename_resource::<Sequence>(&identifier, new_name)
+                        rename::<Sequence>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::Sound => {
+                        rename::<Sound>(yyp_boss, &identifier, new_name, rewrite_usages)
+                    }
+                    Resource::TileSet => {
+                        rename::<TileSet>(yyp_boss, &identifier, new_name, rewrite_usages)
                     }
-                    Resource::Sound => yyp_boss.rename_resource::<Sound>(&identifier, new_name),
-                    Resource::TileSet => yyp_boss.rename_resource::<TileSet>(&identifier, new_name),
                     Resource::Timeline => {
-                        yyp_boss.rename_resource::<Timeline>(&identifier, new_name)
+                        rename::<Timeline>(yyp_boss, &identifier, new_name, rewrite_usages)
                     }
-                };
-
-                match output {
-                    Ok(()) => Ok(CommandOutput::ok()),
-                    Err(e) => Err(YypBossError::ResourceManipulation {
-                        data: e.to_string(),
-                    }),
                 }
             }
             ResourceCommandType::Get { identifier } => match resource_command.resource {
@@ -268,7 +284,7 @@ pub fn parse_command(
                 Resource::Script => create_yy::<Script>(create_data),
                 Resource::Object => create_yy::<Object>(create_data),
                 Resource::Note => create_yy::<Note>(create_data),
-                Resource::Shader => create_yy::<Shader>(create_data),
+                Resource::Shader => create_shader(create_data),
                 Resource::Room => create_yy::<Room>(create_data),
                 Resource::TileSet => create_yy::<TileSet>(create_data),
                 Resource::AnimationCurve
@@ -410,6 +426,63 @@ pub fn parse_command(
                     .can_name_folder(&parent_folder, &identifier)
                     .is_ok(),
             )),
+            UtilityCommand::ExportSprite {
+                sprite_name,
+                format,
+                output_path,
+                game_speed,
+            } => export_sprite(
+                yyp_boss,
+                working_directory,
+                &sprite_name,
+                format,
+                output_path,
+                game_speed,
+            ),
+            UtilityCommand::FindDuplicateFrames { max_distance } => {
+                Ok(CommandOutput::ok_duplicate_frames(
+                    yyp_boss.find_duplicate_frames(max_distance.unwrap_or(4)),
+                ))
+            }
+            UtilityCommand::GmlSymbols { prefix } => match yyp_boss.gml_symbols() {
+                Ok(table) => {
+                    let symbols = match prefix {
+                        Some(prefix) => table.with_prefix(&prefix).cloned().collect(),
+                        None => table.symbols().to_vec(),
+                    };
+                    Ok(CommandOutput::ok_gml_symbols(symbols))
+                }
+                Err(e) => Err(YypBossError::AssociatedDataParseError {
+                    data: e.to_string(),
+                }),
+            },
+            UtilityCommand::FindUsages { name } => match yyp_boss.find_gml_usages(&name) {
+                Ok(usages) => Ok(CommandOutput::ok_gml_usages(usages)),
+                Err(e) => Err(YypBossError::AssociatedDataParseError {
+                    data: e.to_string(),
+                }),
+            },
+            UtilityCommand::SearchGml {
+                pattern,
+                replacement,
+            } => match Regex::new(&pattern) {
+                Ok(pattern) => {
+                    let output = match replacement {
+                        Some(replacement) => yyp_boss.replace_gml(&pattern, &replacement),
+                        None => yyp_boss.search_gml(&pattern),
+                    };
+
+                    match output {
+                        Ok(matches) => Ok(CommandOutput::ok_gml_matches(matches)),
+                        Err(e) => Err(YypBossError::AssociatedDataParseError {
+                            data: e.to_string(),
+                        }),
+                    }
+                }
+                Err(e) => Err(YypBossError::CouldNotReadCommand {
+                    data: e.to_string(),
+                }),
+            },
         },
         Command::Serialize => match yyp_boss.serialize() {
             Ok(()) => Ok(CommandOutput::ok()),
@@ -438,11 +511,46 @@ fn add<T: YyResource>(
     }
 }
 
+fn rename<T: YyResource>(
+    yyp_boss: &mut YypBoss,
+    identifier: &str,
+    new_name: String,
+    rewrite_usages: bool,
+) -> Result<CommandOutput, YypBossError> {
+    let output = if rewrite_usages {
+        yyp_boss
+            .rename_resource_and_usages::<T>(identifier, new_name)
+            .map(CommandOutput::ok_gml_usages)
+    } else {
+        yyp_boss
+            .rename_resource::<T>(identifier, new_name)
+            .map(|()| CommandOutput::ok())
+    };
+
+    output.map_err(|e| YypBossError::ResourceManipulation {
+        data: e.to_string(),
+    })
+}
+
 fn remove<T: YyResource>(
     yyp_boss: &mut YypBoss,
     working_directory: &Utf8Path,
     resource_name: String,
 ) -> Result<CommandOutput, YypBossError> {
+    if yyp_boss.is_placeholder(&resource_name) {
+        return match yyp_boss.remove_placeholder(&resource_name) {
+            Ok(placeholder) => Ok(CommandOutput::ok_resource(SerializedData::Value {
+                data: placeholder
+                    .raw
+                    .map(|v| serde_json::to_string_pretty(&v).unwrap())
+                    .unwrap_or_default(),
+            })),
+            Err(e) => Err(YypBossError::ResourceManipulation {
+                data: e.to_string(),
+            }),
+        };
+    }
+
     match yyp_boss.remove_resource::<T>(&resource_name) {
         Ok(output) => {
             match serialize_yy_data_for_output(&output.0, working_directory, output.1.as_ref()) {
@@ -505,6 +613,38 @@ fn ensure_associated_data<T: YyResource>(
     }
 }
 
+fn export_sprite(
+    yyp_boss: &mut YypBoss,
+    working_directory: &Utf8Path,
+    sprite_name: &str,
+    format: SpriteExportFormat,
+    output_path: PathBuf,
+    game_speed: Option<u32>,
+) -> Result<CommandOutput, YypBossError> {
+    yyp_boss
+        .ensure_associated_data_is_loaded::<Sprite>(sprite_name, false)
+        .map_err(|e| YypBossError::ResourceManipulation {
+            data: e.to_string(),
+        })?;
+
+    let output_path = working_directory.as_std_path().join(output_path);
+    let output = match format {
+        SpriteExportFormat::Sheet => yyp_boss
+            .export_sprite_sheet(sprite_name, &output_path)
+            .map(|_| ()),
+        SpriteExportFormat::Gif => {
+            yyp_boss.export_sprite_gif(sprite_name, &output_path, game_speed.unwrap_or(60) as f64)
+        }
+    };
+
+    match output {
+        Ok(()) => Ok(CommandOutput::ok_path(output_path)),
+        Err(e) => Err(YypBossError::ResourceManipulation {
+            data: e.to_string(),
+        }),
+    }
+}
+
 fn read_new_resource<T: YyResource>(
     new_resource: NewResource,
     tcu: &TrailingCommaUtility,
@@ -557,12 +697,36 @@ fn serialize_yy_data_for_output<T: YyResource>(
 }
 
 fn create_yy<T: YyResource>(cr: CreateCommand) -> Result<CommandOutput, YypBossError> {
+    let yy: T = new_yy(cr);
+
+    Ok(CommandOutput::ok_resource(SerializedData::Value {
+        data: serde_json::to_string_pretty(&yy).unwrap(),
+    }))
+}
+
+fn create_shader(cr: CreateCommand) -> Result<CommandOutput, YypBossError> {
+    let shader_type = cr.shader_type.unwrap_or_default();
+    let mut yy: Shader = new_yy(cr);
+    yy.shader_type = shader_type;
+
+    Ok(CommandOutput::ok_datum(
+        SerializedData::Value {
+            data: serde_json::to_string_pretty(&yy).unwrap(),
+        },
+        Some(SerializedData::Value {
+            data: serde_json::to_string_pretty(&ShaderFile::passthrough(shader_type)).unwrap(),
+        }),
+    ))
+}
+
+fn new_yy<T: YyResource>(cr: CreateCommand) -> T {
     let mut yy = T::default();
 
     let CreateCommand {
         name,
         parent,
         resource: _,
+        shader_type: _,
     } = cr;
 
     if let Some(name) = name {
@@ -573,7 +737,5 @@ fn create_yy<T: YyResource>(cr: CreateCommand) -> Result<CommandOutput, YypBossE
         yy.set_parent_view_path(parent);
     }
 
-    Ok(CommandOutput::ok_resource(SerializedData::Value {
-        data: serde_json::to_string_pretty(&yy).unwrap(),
-    }))
+    yy
 }
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
-use yy_typings::{ViewPath, ViewPathLocation};
+use yy_typings::{ShaderType, ViewPath, ViewPathLocation};
 
-use crate::{Resource, SerializedData};
+use crate::{Resource, SerializedData, SpriteExportFormat};
+use std::path::PathBuf;
 
 /// The type of command to give, pertaining to each of the general areas the YyBoss can give.
 ///
@@ -94,12 +95,18 @@ pub enum ResourceCommandType {
     /// ## Errors
     /// If there isn't a resource by that name of the type given, it will return an error.
     ///
+    /// If `rewriteUsages` is set, every use of the old name in the project's GML is replaced
+    /// with the new name as well.
+    ///
     /// ## Returns
-    /// Returns a `CommandOutput` with no additional data on success.
+    /// Returns a `CommandOutput` with no additional data on success, or with the usages which
+    /// were rewritten if `rewriteUsages` was set.
     #[serde(rename_all = "camelCase")]
     Rename {
         identifier: String,
         new_name: String,
+        #[serde(default)]
+        rewrite_usages: bool,
     },
 
     /// Returns a copy of a resource.
@@ -164,7 +171,7 @@ pub enum ResourceCommandType {
 /// ## Types of Each Field
 ///|   Resource Type  |   new_resource     | associated_data   |
 ///|------------------|-----------------------|----------------------|
-///| [`Sprite`]       |  [`Sprite Yy File`]   | [`HashMap`]<[`Frame Uuid`], [`SpriteImageBuffer`]>  |
+///| [`Sprite`]       |  [`Sprite Yy File`]   | [`SpriteImages`]                                    |
 ///| [`Object`]       |  [`Object Yy File`]   | [`HashMap`]<[`EventType`], [`String`]>              |
 ///| [`Script`]       |  [`Script Yy File`]   | String                                       |
 ///| [`Shader`]       |  [`Shader Yy File`]   | [`ShaderScriptType`] |
@@ -187,7 +194,7 @@ pub enum ResourceCommandType {
 /// [`Object Yy File`]: ../../yy_typings/object_yy/struct.Object.html
 /// [`Script Yy File`]: ../../yy_typings/struct.Script.html
 /// [`Shader Yy File`]: ./error.html
-/// [`Frame Uuid`]: ../../yy_typings/sprite_yy/struct.Frame.html#structfield.name
+/// [`SpriteImages`]: ../struct.SpriteImages.html
 /// [`EventType`]: ../../yy_typings/sprite_yy/object_yy/enum.EventType.html
 /// [`ShaderScriptType`]: ./error.html
 /// [`HashMap`]: ../../../std/collects/struct.HashMap.html
@@ -368,14 +375,79 @@ pub enum UtilityCommand {
         parent_folder: ViewPathLocation,
         identifier: String,
     },
+
+    /// Exports a sprite's frames, either as a sprite sheet PNG with a JSON atlas next to it,
+    /// or as an animated GIF. The sprite's images are loaded if they aren't already.
+    ///
+    /// The `output_path` is relative to the working directory. `game_speed` is the number of game
+    /// frames per second, used to time GIFs of sprites which play back in frames per game frame,
+    /// and defaults to 60.
+    ///
+    /// Returns the path of the exported image.
+    #[serde(rename_all = "camelCase")]
+    ExportSprite {
+        sprite_name: String,
+        format: SpriteExportFormat,
+        output_path: PathBuf,
+        game_speed: Option<u32>,
+    },
+
+    /// Finds frames with identical or similar pixels, and sprites with identical frames,
+    /// across every sprite whose images are loaded. Sprites which aren't loaded are listed
+    /// in the report instead.
+    ///
+    /// `max_distance` is how many bits, out of 64, two frames' perceptual hashes can differ
+    /// by for them to be reported as similar, and defaults to 4.
+    #[serde(rename_all = "camelCase")]
+    FindDuplicateFrames {
+        max_distance: Option<u32>,
+    },
+
+    /// Lists the functions, macros, enums and globalvars declared in the project's scripts,
+    /// object events, room creation code and timeline moments, sorted by name. The code of
+    /// every script and object is loaded if it isn't already.
+    ///
+    /// If a `prefix` is given, only names starting with it are listed.
+    #[serde(rename_all = "camelCase")]
+    GmlSymbols {
+        prefix: Option<String>,
+    },
+
+    /// Finds every place a name, such as a resource's name, is used in the project's scripts,
+    /// object events, room creation code and timeline moments. Uses inside strings and
+    /// comments aren't counted. The code of every script and object is loaded if it isn't
+    /// already.
+    #[serde(rename_all = "camelCase")]
+    FindUsages {
+        name: String,
+    },
+
+    /// Searches the project's scripts, object events, room creation code and timeline moments
+    /// with a regular expression, returning every match. The code of every script and object
+    /// is loaded if it isn't already.
+    ///
+    /// If a `replacement` is given, every match is replaced with it, and the changed resources
+    /// will be written on the next `Serialize`. The replacement can use the pattern's capture
+    /// groups, such as `$1`. The matches returned are where they were before the replacement.
+    #[serde(rename_all = "camelCase")]
+    SearchGml {
+        pattern: String,
+        replacement: Option<String>,
+    },
 }
 
 /// A create command for the Yy to process.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CreateCommand {
     pub resource: Resource,
     pub name: Option<String>,
     pub parent: Option<ViewPath>,
+
+    /// The language of a new shader, written as in a `.yy` file: `1` for GLSL ES, `2` for
+    /// GLSL, and `3` for HLSL 11. Defaults to GLSL ES. Shaders are created with a passthrough
+    /// vertex and pixel shader in that language as their associated data.
+    pub shader_type: Option<ShaderType>,
 }
 
 #[cfg(test)]
@@ -417,6 +489,14 @@ mod tests {
             resource: Resource::Script,
             name: Some("jim".to_string()),
             parent: None,
+            shader_type: None,
+        })));
+
+        harness(Command::Utilities(UtilityCommand::Create(CreateCommand {
+            resource: Resource::Shader,
+            name: Some("shd_outline".to_string()),
+            parent: None,
+            shader_type: Some(ShaderType::Hlsl),
         })));
 
         harness(Command::Utilities(UtilityCommand::PrettyEventNames {
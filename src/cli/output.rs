@@ -1,10 +1,9 @@
-use log::error;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use thiserror::Error;
 use crate::{
     folders::{FlatFolderGraph, Item},
-    ProjectMetadata, SerializedData,
+    DuplicateReport, GmlMatch, GmlSymbol, GmlUsage, ProjectMetadata, SerializedData,
 };
 use yy_typings::ViewPath;
 
@@ -72,6 +71,18 @@ pub struct CommandOutput {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub project_metadata: Option<ProjectMetadata>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_frames: Option<DuplicateReport>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gml_symbols: Option<Vec<GmlSymbol>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gml_usages: Option<Vec<GmlUsage>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gml_matches: Option<Vec<GmlMatch>>,
 }
 
 impl CommandOutput {
@@ -178,6 +189,38 @@ impl CommandOutput {
             ..Self::default()
         }
     }
+
+    pub fn ok_duplicate_frames(report: DuplicateReport) -> Self {
+        Self {
+            success: true,
+            duplicate_frames: Some(report),
+            ..Self::default()
+        }
+    }
+
+    pub fn ok_gml_symbols(symbols: Vec<GmlSymbol>) -> Self {
+        Self {
+            success: true,
+            gml_symbols: Some(symbols),
+            ..Self::default()
+        }
+    }
+
+    pub fn ok_gml_usages(usages: Vec<GmlUsage>) -> Self {
+        Self {
+            success: true,
+            gml_usages: Some(usages),
+            ..Self::default()
+        }
+    }
+
+    pub fn ok_gml_matches(matches: Vec<GmlMatch>) -> Self {
+        Self {
+            success: true,
+            gml_matches: Some(matches),
+            ..Self::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
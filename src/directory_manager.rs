@@ -15,6 +15,8 @@ pub struct DirectoryManager {
 }
 
 impl DirectoryManager {
+    const BOSS_DIRECTORY: &'static str = ".yyboss";
+
     /// Creates a new Directory manager and initializes the boss directory, if it doesn't exist.
     pub(crate) fn new(yyp: &Path) -> Result<Self, StartupError> {
         let root_directory = yyp
@@ -43,6 +45,12 @@ impl DirectoryManager {
         &self.yyp
     }
 
+    /// Returns the path to the boss directory, a hidden directory next to the yyp where
+    /// we keep our own files, such as the load cache. It may not exist yet.
+    pub fn boss_directory(&self) -> PathBuf {
+        self.root_directory.join(Self::BOSS_DIRECTORY)
+    }
+
     /// Creates a path within the root directory, probably to a resource.
     pub fn resource_file(&self, relative_path: &Path) -> PathBuf {
         self.root_directory.join(relative_path)
@@ -72,6 +72,26 @@ impl<R: std::hash::Hash + Eq + Clone, A: Default> DirtyHandler<R, A> {
         self.resources_to_reserialize.insert(new_value, dirty_state);
     }
 
+    /// Marks the value as edited, and adds to its associated values, such as files to clean up.
+    /// Unlike `replace_associated`, this adds to the associated values even if the value was
+    /// already edited. If the value is new, there's nothing on disk yet, so nothing is added.
+    pub fn edit_associated<F>(&mut self, new_value: R, f: F)
+    where
+        F: FnOnce(DirtyValueHolder<'_, A>),
+    {
+        if self.resources_to_reserialize.get(&new_value) != Some(&DirtyState::New) {
+            let inner = self
+                .associated_values
+                .as_mut()
+                .unwrap()
+                .entry(new_value.clone())
+                .or_default();
+            f(DirtyValueHolder(inner));
+        }
+
+        self.edit(new_value);
+    }
+
     pub fn edit(&mut self, new_value: R) {
         let dirty_state = match self.resources_to_reserialize.remove(&new_value) {
             Some(DirtyState::New) => DirtyState::New,
@@ -0,0 +1,308 @@
+use crate::{SpriteImageBuffer, SpriteImages};
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+use yy_typings::{FrameId, Sprite};
+
+/// Frames and sprites which are identical, or nearly so, across a project.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateReport {
+    /// Groups of frames with exactly the same pixels, each of which has at least two frames.
+    pub identical_frames: Vec<Vec<FrameRef>>,
+
+    /// Pairs of frames which look alike, but aren't identical, with how different they are.
+    /// Frames are compared by a 64 bit perceptual hash, so the distance is between 0 and 64.
+    /// Identical frames are only listed once, by the first frame of their group.
+    pub similar_frames: Vec<SimilarFrames>,
+
+    /// Groups of sprites whose frames are all identical, in the same order.
+    pub identical_sprites: Vec<Vec<String>>,
+
+    /// Sprites which weren't checked because their images aren't loaded.
+    pub unloaded: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameRef {
+    pub sprite: String,
+    pub frame: FrameId,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarFrames {
+    pub first: FrameRef,
+    pub second: FrameRef,
+    pub distance: u32,
+}
+
+struct HashedFrame<'a> {
+    frame: FrameRef,
+    image: &'a SpriteImageBuffer,
+    exact: u64,
+    perceptual: u64,
+}
+
+/// Finds duplicate frames and sprites. Frames whose perceptual hashes are at most
+/// `max_distance` bits apart are reported as similar; a `max_distance` of `0` still finds
+/// frames which are nearly, but not exactly, identical. The hashes are 64 bits, so a
+/// `max_distance` past 64 is the same as 64.
+pub fn find_duplicates(sprites: &[(&Sprite, &SpriteImages)], max_distance: u32) -> DuplicateReport {
+    let max_distance = max_distance.min(64);
+    let mut frames = vec![];
+    for (sprite, images) in sprites {
+        for (index, frame) in sprite.frames.iter().enumerate() {
+            if let Some(image) = images.frames.get(&frame.name) {
+                frames.push(HashedFrame {
+                    frame: FrameRef {
+                        sprite: sprite.common_data.name.clone(),
+                        frame: frame.name,
+                        index,
+                    },
+                    image,
+                    exact: exact_hash(image),
+                    perceptual: perceptual_hash(image),
+                });
+            }
+        }
+    }
+
+    // hashes can collide, so we only group frames which really are the same
+    let mut groups: Vec<Vec<usize>> = vec![];
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut group_of = vec![0; frames.len()];
+    for (i, frame) in frames.iter().enumerate() {
+        let candidates = by_hash.entry(frame.exact).or_default();
+        let existing = candidates
+            .iter()
+            .copied()
+            .find(|group| frames[groups[*group][0]].image == frame.image);
+
+        group_of[i] = match existing {
+            Some(group) => {
+                groups[group].push(i);
+                group
+            }
+            None => {
+                groups.push(vec![i]);
+                candidates.push(groups.len() - 1);
+                groups.len() - 1
+            }
+        };
+    }
+
+    let identical_frames = groups
+        .iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| group.iter().map(|i| frames[*i].frame.clone()).collect())
+        .collect();
+
+    // identical frames look alike too, so we only compare the first frame of each group. Two
+    // hashes which are close enough share at least one pigeonhole key, so only frames which
+    // share a key are compared, and a pair is only reported from the first key they share.
+    let hash_of = |group: usize| frames[groups[group][0]].perceptual;
+    let mut by_key: HashMap<(u32, u64), Vec<usize>> = HashMap::new();
+    for group in 0..groups.len() {
+        for key in pigeonhole_keys(hash_of(group), max_distance) {
+            by_key.entry(key).or_default().push(group);
+        }
+    }
+
+    let mut similar_frames = vec![];
+    for first in 0..groups.len() {
+        let first_hash = hash_of(first);
+        let mut similar = vec![];
+
+        for (part, key) in pigeonhole_keys(first_hash, max_distance) {
+            // groups are pushed in order, so the later groups are at the end
+            let groups_with_key = &by_key[&(part, key)];
+            let later = groups_with_key.partition_point(|group| *group <= first);
+
+            for &second in &groups_with_key[later..] {
+                let second_hash = hash_of(second);
+                let distance = (first_hash ^ second_hash).count_ones();
+                if distance > max_distance {
+                    continue;
+                }
+
+                let first_shared = pigeonhole_keys(first_hash, max_distance)
+                    .zip(pigeonhole_keys(second_hash, max_distance))
+                    .position(|(a, b)| a == b);
+                if first_shared == Some(part as usize) {
+                    similar.push((second, distance));
+                }
+            }
+        }
+
+        similar.sort_unstable();
+        similar_frames.extend(similar.into_iter().map(|(second, distance)| SimilarFrames {
+            first: frames[groups[first][0]].frame.clone(),
+            second: frames[groups[second][0]].frame.clone(),
+            distance,
+        }));
+    }
+
+    // a sprite is its frame groups, in order. A sprite's frames are all next to each other
+    let mut sprites_by_frames: HashMap<Vec<usize>, Vec<String>> = HashMap::new();
+    let mut start = 0;
+    for sprite_frames in frames.chunk_by(|a, b| a.frame.sprite == b.frame.sprite) {
+        let end = start + sprite_frames.len();
+        sprites_by_frames
+            .entry(group_of[start..end].to_vec())
+            .or_default()
+            .push(sprite_frames[0].frame.sprite.clone());
+        start = end;
+    }
+    let mut identical_sprites: Vec<Vec<String>> = sprites_by_frames
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .collect();
+    identical_sprites.sort();
+
+    DuplicateReport {
+        identical_frames,
+        similar_frames,
+        identical_sprites,
+        unloaded: vec![],
+    }
+}
+
+/// Splits a hash into `max_distance + 1` parts, keyed by their position. By the pigeonhole
+/// principle, two hashes at most `max_distance` bits apart have at least one part in common.
+/// A hash can't be split into more than 64 parts, so past that, every hash has the same key.
+fn pigeonhole_keys(hash: u64, max_distance: u32) -> impl Iterator<Item = (u32, u64)> {
+    let everything = max_distance >= 64;
+    let parts = if everything { 1 } else { max_distance + 1 };
+
+    (0..parts).map(move |part| {
+        if everything {
+            return (0, 0);
+        }
+
+        let start = part * 64 / parts;
+        let end = (part + 1) * 64 / parts;
+        let mask = u64::MAX >> (64 - (end - start));
+
+        (part, (hash >> start) & mask)
+    })
+}
+
+fn exact_hash(image: &SpriteImageBuffer) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    image.as_raw().hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// A difference hash: the image is shrunk to 9x8, and each bit is whether a pixel is brighter
+/// than the one to its right. Transparent pixels count as black.
+fn perceptual_hash(image: &SpriteImageBuffer) -> u64 {
+    let small = image::imageops::resize(image, 9, 8, FilterType::Triangle);
+    let brightness = |x: u32, y: u32| {
+        let p = small.get_pixel(x, y);
+        let luma = 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32;
+        luma * p[3] as f32 / 255.0
+    };
+
+    let mut output = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            output <<= 1;
+            if brightness(x, y) > brightness(x + 1, y) {
+                output |= 1;
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite_from_frames;
+    use image::Rgba;
+    use yy_typings::{TexturePath, ViewPath};
+
+    fn gradient(shift: u8) -> SpriteImageBuffer {
+        SpriteImageBuffer::from_fn(16, 16, |x, _| {
+            let v = (x * 16) as u8;
+            Rgba([v.saturating_add(shift), v, v, 255])
+        })
+    }
+
+    #[test]
+    fn duplicates() {
+        let stripes = SpriteImageBuffer::from_fn(16, 16, |x, _| {
+            let v = if x % 2 == 0 { 255 } else { 0 };
+            Rgba([v, v, v, 255])
+        });
+
+        let new = |name: &str, frames: Vec<SpriteImageBuffer>| {
+            sprite_from_frames(name, TexturePath::default(), ViewPath::default(), frames).unwrap()
+        };
+        let a = new("spr_a", vec![gradient(0), stripes.clone()]);
+        let b = new("spr_b", vec![gradient(0), stripes]);
+        let c = new("spr_c", vec![gradient(1)]);
+        let sprites = [(&a.0, &a.1), (&b.0, &b.1), (&c.0, &c.1)];
+
+        let report = find_duplicates(&sprites, 2);
+        assert_eq!(report.identical_frames.len(), 2);
+        assert_eq!(report.identical_frames[0][0].sprite, "spr_a");
+        assert_eq!(report.identical_frames[0][1].sprite, "spr_b");
+        assert_eq!(
+            report.identical_sprites,
+            vec![vec!["spr_a".to_string(), "spr_b".to_string()]]
+        );
+
+        // the slightly redder gradient is similar to the plain ones, but nothing else is
+        assert_eq!(report.similar_frames.len(), 1);
+        assert_eq!(report.similar_frames[0].first.sprite, "spr_a");
+        assert_eq!(report.similar_frames[0].first.index, 0);
+        assert_eq!(report.similar_frames[0].second.sprite, "spr_c");
+
+        // every pair is close enough when the distance covers the whole hash
+        assert_eq!(find_duplicates(&sprites, 64).similar_frames.len(), 3);
+        assert_eq!(
+            find_duplicates(&sprites, u32::MAX).similar_frames,
+            find_duplicates(&sprites, 64).similar_frames
+        );
+    }
+
+    #[test]
+    fn pairs_are_reported_once() {
+        // frames which are close share many keys, but are only reported from the first
+        let images = (0..6).map(|shift| gradient(shift * 2)).collect();
+        let sprite = sprite_from_frames(
+            "spr_gradients",
+            TexturePath::default(),
+            ViewPath::default(),
+            images,
+        )
+        .unwrap();
+
+        for max_distance in [0, 1, 8, 63, 64] {
+            let report = find_duplicates(&[(&sprite.0, &sprite.1)], max_distance);
+            let mut pairs: Vec<_> = report
+                .similar_frames
+                .iter()
+                .map(|v| (v.first.index, v.second.index))
+                .collect();
+            assert!(pairs.iter().all(|(first, second)| first < second));
+
+            let len = pairs.len();
+            pairs.dedup();
+            assert_eq!(pairs.len(), len);
+            if max_distance == 64 {
+                assert_eq!(len, 15);
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{folders::FolderGraphError, FileSerializationError, SerializedDataError};
+use crate::{
+    folders::FolderGraphError, FileSerializationError, IdeVersionError, Resource,
+    SerializedDataError,
+};
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -13,6 +16,16 @@ pub enum StartupError {
     #[error("yyp is wrong version -- needed {0}, got {1}")]
     YypDoesNotMatch(semver::VersionReq, semver::Version),
 
+    #[error("yyp version is outside the accepted range -- needed {min} to {max}, got {found}")]
+    YypVersionOutOfRange {
+        min: String,
+        max: String,
+        found: String,
+    },
+
+    #[error("yyp has a malformed ide version -- {0}")]
+    BadYypVersion(IdeVersionError),
+
     #[error("couldn't make or find the boss directory -- {0}")]
     BossDirectory(String),
 
@@ -60,6 +73,12 @@ pub enum ResourceManipulationError {
 
     #[error("resource cannot be manipulated yet -- yyp-boss does not have full support yet. please file an issue")]
     ResourceCannotBeManipulated,
+
+    #[error("resource failed to load and is a read-only placeholder -- it can only be moved or removed")]
+    ResourceIsPlaceholder,
+
+    #[error("couldn't load the project's gml -- {0}")]
+    CouldNotLoadGml(String),
 }
 
 #[derive(Debug, Error)]
@@ -75,4 +94,28 @@ pub enum YyResourceHandlerError {
 
     #[error("we cannot force serialization because the associated data could not be found")]
     CannotForceSerialization,
+
+    #[error("the associated data of the resource must be loaded to do this")]
+    AssociatedDataNotLoaded,
+
+    #[error("bad edit -- {0}")]
+    BadEdit(String),
+}
+
+/// Every resource which failed to serialize in a [`YypBoss::serialize`]. Serialization
+/// does not stop at the first failure, so there may be many.
+///
+/// [`YypBoss::serialize`]: crate::YypBoss::serialize
+#[derive(Debug, Error, Default)]
+#[error("couldn't serialize {} resources -- {}", .failures.len(), .failures.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "))]
+pub struct SerializationErrors {
+    pub failures: Vec<SerializationFailure>,
+}
+
+#[derive(Debug, Error)]
+#[error("{resource} {name}: {error}")]
+pub struct SerializationFailure {
+    pub name: String,
+    pub resource: Resource,
+    pub error: anyhow::Error,
 }
@@ -79,6 +79,28 @@ impl Vfs {
         Ok(())
     }
 
+    /// Loads in a resource which could not be loaded as a placeholder. If the parent
+    /// folder given cannot be found, the placeholder is put at the root of the project instead.
+    pub(crate) fn load_in_placeholder(
+        &mut self,
+        name: &str,
+        resource: Resource,
+        parent: &ViewPathLocation,
+    ) -> ViewPathLocation {
+        let parent = if Self::get_folder_inner(&self.root, parent).is_some() {
+            parent.clone()
+        } else {
+            self.root_file_viewpath()
+        };
+
+        let folder = Vfs::get_folder_mut(&mut self.root, &parent).expect("root always exists");
+        folder
+            .files
+            .load_in_placeholder(name, resource, parent.clone(), &mut self.resource_names);
+
+        parent
+    }
+
     pub(crate) fn get_folder_mut<'a>(
         root: &'a mut FolderGraph,
         view_path: &ViewPathLocation,
@@ -526,6 +548,8 @@ impl Vfs {
         let dest = Self::get_folder_mut(&mut self.root, new_parent).unwrap();
 
         dest.files.attach(path);
+        self.resource_names
+            .set_parent_location(resource_to_move, new_parent.clone());
 
         Ok(())
     }
@@ -793,6 +817,14 @@ impl ResourceNames {
         self.names.get(name)
     }
 
+    /// Updates where a resource lives in the Vfs. This doesn't change the yyp's
+    /// listing of the resource, so we don't need to mark it as dirty.
+    pub(crate) fn set_parent_location(&mut self, name: &str, parent_location: ViewPathLocation) {
+        if let Some(desc) = self.names.get_mut(name) {
+            desc.parent_location = parent_location;
+        }
+    }
+
     /// Returns all the currently known names and descriptors in the project.
     pub fn inner(&self) -> &HashMap<String, ResourceDescriptor> {
         &self.names
@@ -853,6 +885,13 @@ impl ResourceNames {
 pub struct ResourceDescriptor {
     pub resource: Resource,
     pub parent_location: ViewPathLocation,
+
+    /// If this resource failed to load and is only held as a read-only placeholder.
+    /// Placeholders can be moved or removed, but nothing else. See [`Placeholder`].
+    ///
+    /// [`Placeholder`]: crate::Placeholder
+    #[serde(default)]
+    pub placeholder: bool,
 }
 
 impl ResourceDescriptor {
@@ -860,6 +899,15 @@ impl ResourceDescriptor {
         Self {
             resource,
             parent_location: view_path_location,
+            placeholder: false,
+        }
+    }
+
+    pub fn new_placeholder(resource: Resource, view_path_location: ViewPathLocation) -> Self {
+        Self {
+            resource,
+            parent_location: view_path_location,
+            placeholder: true,
         }
     }
 
@@ -896,6 +944,23 @@ impl Files {
         );
     }
 
+    pub fn load_in_placeholder(
+        &mut self,
+        name: &str,
+        resource: Resource,
+        parent_location: ViewPathLocation,
+        rn: &mut ResourceNames,
+    ) {
+        self.0
+            .push(FilesystemPath::new(resource.subpath_name(), name));
+
+        // add to resource names...
+        rn.load_in_resource(
+            name.to_string(),
+            ResourceDescriptor::new_placeholder(resource, parent_location),
+        );
+    }
+
     pub fn add<T: YyResource>(&mut self, yy: &T, rn: &mut ResourceNames) {
         self.attach(FilesystemPath::new(T::SUBPATH_NAME, yy.name()));
 
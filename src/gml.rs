@@ -0,0 +1,13 @@
+pub(crate) mod lexer;
+
+mod symbols;
+pub use symbols::*;
+
+mod usages;
+pub use usages::*;
+
+mod search;
+pub use search::*;
+
+mod plain_gml;
+pub use plain_gml::*;
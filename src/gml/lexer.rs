@@ -0,0 +1,227 @@
+/// A very small GML lexer, which is only precise enough to find declarations and names. It's
+/// close enough to GLSL and HLSL to read shaders' declarations too. It skips whitespace and
+/// comments, and never fails: anything it doesn't understand becomes punctuation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+
+    /// The byte offset of the token in the source.
+    pub offset: usize,
+
+    /// The line and column of the token, both starting at 1. Columns count characters.
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Identifier,
+    Number,
+    String,
+    Punctuation,
+
+    /// A line starting with `#`, such as `#macro` or `#region`, including any lines it's
+    /// continued onto with a `\`.
+    Directive,
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+
+    /// Whether only whitespace has been seen since the last newline.
+    line_start: bool,
+}
+
+pub(crate) fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut lexer = Lexer {
+        source,
+        offset: 0,
+        line: 1,
+        column: 1,
+        line_start: true,
+    };
+    let mut output = vec![];
+
+    while let Some(c) = lexer.peek(0) {
+        let start = (lexer.offset, lexer.line, lexer.column);
+
+        let kind = match c {
+            c if c.is_whitespace() => {
+                lexer.bump();
+                continue;
+            }
+            '/' if lexer.peek(1) == Some('/') => {
+                lexer.bump_while(|c| c != '\n');
+                continue;
+            }
+            '/' if lexer.peek(1) == Some('*') => {
+                lexer.bump();
+                lexer.bump();
+                while lexer.peek(0).is_some() && lexer.rest().starts_with("*/") == false {
+                    lexer.bump();
+                }
+                lexer.bump();
+                lexer.bump();
+                continue;
+            }
+            '#' if lexer.line_start => {
+                lexer.directive();
+                TokenKind::Directive
+            }
+            '"' | '\'' => {
+                lexer.bump();
+                lexer.string(c, true);
+                TokenKind::String
+            }
+            '@' if matches!(lexer.peek(1), Some('"' | '\'')) => {
+                let quote = lexer.peek(1).unwrap();
+                lexer.bump();
+                lexer.bump();
+                lexer.string(quote, false);
+                TokenKind::String
+            }
+            '$' if lexer.peek(1) == Some('"') => {
+                lexer.bump();
+                lexer.bump();
+                lexer.string('"', true);
+                TokenKind::String
+            }
+            '$' if lexer.peek(1).is_some_and(|c| c.is_ascii_hexdigit()) => {
+                lexer.bump();
+                lexer.bump_while(|c| c.is_ascii_hexdigit());
+                TokenKind::Number
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' && lexer.peek(1).is_some_and(|c| c.is_ascii_digit())) =>
+            {
+                lexer.bump_while(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+                TokenKind::Number
+            }
+            c if is_identifier_start(c) => {
+                lexer.bump_while(is_identifier_char);
+                TokenKind::Identifier
+            }
+            _ => {
+                lexer.bump();
+                TokenKind::Punctuation
+            }
+        };
+
+        lexer.line_start = false;
+        output.push(Token {
+            kind,
+            text: &source[start.0..lexer.offset],
+            offset: start.0,
+            line: start.1,
+            column: start.2,
+        });
+    }
+
+    output
+}
+
+pub(crate) fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+pub(crate) fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.offset += c.len_utf8();
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+            self.line_start = true;
+        } else {
+            self.column += 1;
+            if c.is_whitespace() == false {
+                self.line_start = false;
+            }
+        }
+
+        Some(c)
+    }
+
+    fn bump_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.peek(0).is_some_and(&f) {
+            self.bump();
+        }
+    }
+
+    /// Eats the rest of a string, after its opening quote. Unterminated strings end at the
+    /// end of the source.
+    fn string(&mut self, quote: char, escapes: bool) {
+        while let Some(c) = self.bump() {
+            if c == quote {
+                break;
+            }
+            if escapes && c == '\\' {
+                self.bump();
+            }
+        }
+    }
+
+    /// Eats a directive to the end of its line, following `\` continuations.
+    fn directive(&mut self) {
+        loop {
+            self.bump_while(|c| c != '\n');
+
+            let line = &self.source[..self.offset];
+            if line.trim_end_matches('\r').ends_with('\\') && self.peek(0).is_some() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let source =
+            "#macro A 1\nvar s = \"spr_a // not a comment\"; // comment\n/* x */ y = $FF + .5;";
+        let tokens: Vec<(TokenKind, &str, usize, usize)> = tokenize(source)
+            .into_iter()
+            .map(|v| (v.kind, v.text, v.line, v.column))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Directive, "#macro A 1", 1, 1),
+                (TokenKind::Identifier, "var", 2, 1),
+                (TokenKind::Identifier, "s", 2, 5),
+                (TokenKind::Punctuation, "=", 2, 7),
+                (TokenKind::String, "\"spr_a // not a comment\"", 2, 9),
+                (TokenKind::Punctuation, ";", 2, 33),
+                (TokenKind::Identifier, "y", 3, 9),
+                (TokenKind::Punctuation, "=", 3, 11),
+                (TokenKind::Number, "$FF", 3, 13),
+                (TokenKind::Punctuation, "+", 3, 17),
+                (TokenKind::Number, ".5", 3, 19),
+                (TokenKind::Punctuation, ";", 3, 21),
+            ]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use super::GmlSource;
+use crate::{
+    directory_manager::DirectoryManager, FileSerializationError, Resource, SerializationFailure,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A GML file which isn't part of any resource's associated data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlainGml {
+    pub source: GmlSource,
+    pub gml: String,
+}
+
+/// The GML of rooms and timelines. Those resources don't have typings for their code, so we
+/// read and write their GML files directly, and leave their yy files alone.
+#[derive(Debug, PartialEq, Default)]
+pub struct PlainGmlHandler {
+    files: BTreeMap<PathBuf, PlainGml>,
+    loaded: HashSet<PathBuf>,
+    edited: BTreeSet<PathBuf>,
+}
+
+impl PlainGmlHandler {
+    /// Every loaded file, by its path relative to the project's root directory.
+    pub fn files(&self) -> &BTreeMap<PathBuf, PlainGml> {
+        &self.files
+    }
+
+    /// Reads a room's creation code, and the creation code of the instances in it, if it
+    /// isn't loaded yet. `directory` is relative to the project's root directory.
+    pub(crate) fn load_room(
+        &mut self,
+        directory_manager: &DirectoryManager,
+        room: &str,
+        directory: &Path,
+    ) -> Result<(), FileSerializationError> {
+        self.load_directory(directory_manager, directory, |file| {
+            let creation_code =
+                file == "RoomCreationCode" || file.starts_with("InstanceCreationCode_");
+
+            creation_code.then(|| GmlSource::RoomCreationCode {
+                room: room.to_string(),
+                file: file.to_string(),
+            })
+        })
+    }
+
+    /// Reads a timeline's moments, if they aren't loaded yet. `directory` is relative to the
+    /// project's root directory.
+    pub(crate) fn load_timeline(
+        &mut self,
+        directory_manager: &DirectoryManager,
+        timeline: &str,
+        directory: &Path,
+    ) -> Result<(), FileSerializationError> {
+        self.load_directory(directory_manager, directory, |file| {
+            file.starts_with("moment_")
+                .then(|| GmlSource::TimelineMoment {
+                    timeline: timeline.to_string(),
+                    moment: file.to_string(),
+                })
+        })
+    }
+
+    /// Reads every GML file in the directory which `source` gives a source for, by its name
+    /// without the extension.
+    fn load_directory(
+        &mut self,
+        directory_manager: &DirectoryManager,
+        directory: &Path,
+        source: impl Fn(&str) -> Option<GmlSource>,
+    ) -> Result<(), FileSerializationError> {
+        if self.loaded.contains(directory) {
+            return Ok(());
+        }
+
+        let io = |e: std::io::Error| FileSerializationError::Io(e.to_string());
+        let absolute_directory = directory_manager.resource_file(directory);
+
+        // a resource without any code might not have a directory of its own
+        if absolute_directory.is_dir() {
+            for entry in fs::read_dir(&absolute_directory).map_err(io)? {
+                let path = entry.map_err(io)?.path();
+                if path.extension().is_some_and(|v| v == "gml") == false {
+                    continue;
+                }
+                let Some(source) = path.file_stem().and_then(|v| v.to_str()).and_then(&source)
+                else {
+                    continue;
+                };
+
+                let gml = fs::read_to_string(&path).map_err(io)?;
+                let relative_path = directory.join(path.file_name().unwrap());
+                self.files.insert(relative_path, PlainGml { source, gml });
+            }
+        }
+
+        self.loaded.insert(directory.to_owned());
+
+        Ok(())
+    }
+
+    /// Forgets the files of a removed room or timeline, so they're no longer searched or
+    /// written. `directory` is the resource's directory, relative to the project's root.
+    pub(crate) fn remove_resource(&mut self, resource: Resource, name: &str, directory: &Path) {
+        self.files
+            .retain(|_, file| file.source.resource() != (resource, name));
+        self.edited.retain(|path| self.files.contains_key(path));
+        self.loaded.remove(directory);
+    }
+
+    /// Moves the files of a renamed room or timeline into its new directory. They're all
+    /// marked for serialization, since the resource's files now live in that directory.
+    pub(crate) fn rename_resource(
+        &mut self,
+        resource: Resource,
+        name: &str,
+        new_name: &str,
+        directory: &Path,
+        new_directory: &Path,
+    ) {
+        let paths: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|(_, file)| file.source.resource() == (resource, name))
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in paths {
+            let mut file = self.files.remove(&path).unwrap();
+            self.edited.remove(&path);
+
+            match &mut file.source {
+                GmlSource::RoomCreationCode { room, .. } => *room = new_name.to_string(),
+                GmlSource::TimelineMoment { timeline, .. } => *timeline = new_name.to_string(),
+                GmlSource::Script { .. } | GmlSource::ObjectEvent { .. } => {}
+            }
+
+            let new_path = new_directory.join(path.file_name().unwrap());
+            self.edited.insert(new_path.clone());
+            self.files.insert(new_path, file);
+        }
+
+        if self.loaded.remove(directory) {
+            self.loaded.insert(new_directory.to_owned());
+        }
+    }
+
+    /// Rewrites the code of every loaded file, marking the ones which changed for
+    /// serialization.
+    pub(crate) fn rewrite(&mut self, rewrite: impl Fn(&str) -> String) {
+        for (path, file) in self.files.iter_mut() {
+            let rewritten = rewrite(&file.gml);
+            if rewritten != file.gml {
+                file.gml = rewritten;
+                self.edited.insert(path.clone());
+            }
+        }
+    }
+
+    /// Writes every edited file. Files which couldn't be written stay marked, so the next
+    /// serialization retries them.
+    pub(crate) fn serialize(
+        &mut self,
+        directory_manager: &DirectoryManager,
+    ) -> Vec<SerializationFailure> {
+        let mut failures = vec![];
+
+        for path in std::mem::take(&mut self.edited) {
+            let file = &self.files[&path];
+            if let Err(e) = fs::write(directory_manager.resource_file(&path), &file.gml) {
+                let (resource, name) = file.source.resource();
+                failures.push(SerializationFailure {
+                    name: name.to_string(),
+                    resource,
+                    error: anyhow::Error::new(e).context(format!("couldn't write {:?}", path)),
+                });
+
+                self.edited.insert(path);
+            }
+        }
+
+        failures
+    }
+}
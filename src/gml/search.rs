@@ -0,0 +1,79 @@
+use super::{GmlPosition, GmlSource};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A match of a pattern in a project's GML.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmlMatch {
+    pub source: GmlSource,
+
+    /// The GML file, relative to the project's root directory.
+    pub path: PathBuf,
+
+    #[serde(flatten)]
+    pub position: GmlPosition,
+
+    /// The text which was matched.
+    pub text: String,
+}
+
+/// Finds every match of a pattern in some GML, in order. Unlike [`find_identifier`], this
+/// is plain text search, so strings and comments are searched too.
+///
+/// [`find_identifier`]: crate::find_identifier
+pub fn find_pattern<'a>(gml: &'a str, pattern: &Regex) -> Vec<(GmlPosition, &'a str)> {
+    let mut output = vec![];
+    let mut position = GmlPosition { line: 1, column: 1 };
+    let mut last = 0;
+
+    for found in pattern.find_iter(gml) {
+        // we only walk the text between matches once
+        for c in gml[last..found.start()].chars() {
+            if c == '\n' {
+                position.line += 1;
+                position.column = 1;
+            } else {
+                position.column += 1;
+            }
+        }
+        last = found.start();
+
+        output.push((position, found.as_str()));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern() {
+        let gml = "hp = 10;\nmax_hp = 10; // hp\nshow_debug_message(\"é hp\");";
+        let pattern = Regex::new(r"\bhp\b").unwrap();
+
+        assert_eq!(
+            find_pattern(gml, &pattern),
+            vec![
+                (GmlPosition { line: 1, column: 1 }, "hp"),
+                (
+                    GmlPosition {
+                        line: 2,
+                        column: 17
+                    },
+                    "hp"
+                ),
+                (
+                    GmlPosition {
+                        line: 3,
+                        column: 23
+                    },
+                    "hp"
+                ),
+            ]
+        );
+    }
+}
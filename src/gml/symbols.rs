@@ -0,0 +1,445 @@
+use super::lexer::{tokenize, Token, TokenKind};
+use crate::Resource;
+use serde::{Deserialize, Serialize};
+
+/// Something declared in GML which can be used from anywhere in a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmlDeclaration {
+    pub name: String,
+    pub kind: GmlDeclarationKind,
+
+    /// Where the declaration's name is, both starting at 1.
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GmlDeclarationKind {
+    #[serde(rename_all = "camelCase")]
+    Function {
+        parameters: Vec<String>,
+        constructor: bool,
+    },
+
+    /// A `#macro`, whose value is kept as written. Macros which only apply to one
+    /// configuration, such as `#macro Debug:LOGGING true`, name it.
+    #[serde(rename_all = "camelCase")]
+    Macro {
+        value: String,
+        configuration: Option<String>,
+    },
+
+    #[serde(rename_all = "camelCase")]
+    Enum {
+        members: Vec<String>,
+    },
+
+    GlobalVar,
+}
+
+/// Where a declaration was found.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GmlSource {
+    Script {
+        name: String,
+    },
+
+    /// An event of an object, named by its file name, such as `Create_0`.
+    ObjectEvent {
+        object: String,
+        event: String,
+    },
+
+    /// The creation code of a room, or of an instance in it, named by its file name, such as
+    /// `RoomCreationCode` or `InstanceCreationCode_inst_5B3A1C2D`.
+    RoomCreationCode {
+        room: String,
+        file: String,
+    },
+
+    /// A moment of a timeline, named by its file name, such as `moment_30`.
+    TimelineMoment {
+        timeline: String,
+        moment: String,
+    },
+}
+
+impl GmlSource {
+    /// The kind and name of the resource which the GML belongs to.
+    pub fn resource(&self) -> (Resource, &str) {
+        match self {
+            GmlSource::Script { name } => (Resource::Script, name),
+            GmlSource::ObjectEvent { object, .. } => (Resource::Object, object),
+            GmlSource::RoomCreationCode { room, .. } => (Resource::Room, room),
+            GmlSource::TimelineMoment { timeline, .. } => (Resource::Timeline, timeline),
+        }
+    }
+}
+
+/// A declaration, and where it was found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmlSymbol {
+    #[serde(flatten)]
+    pub declaration: GmlDeclaration,
+    pub source: GmlSource,
+}
+
+/// Every declaration in a set of GML sources, sorted by name.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SymbolTable {
+    symbols: Vec<GmlSymbol>,
+}
+
+impl SymbolTable {
+    /// Scans every source for declarations.
+    pub fn new<'a>(sources: impl IntoIterator<Item = (GmlSource, &'a str)>) -> Self {
+        let mut symbols: Vec<GmlSymbol> = sources
+            .into_iter()
+            .flat_map(|(source, gml)| {
+                scan_declarations(gml)
+                    .into_iter()
+                    .map(move |declaration| GmlSymbol {
+                        declaration,
+                        source: source.clone(),
+                    })
+            })
+            .collect();
+        symbols.sort_by(|a, b| {
+            (&a.declaration.name, a.declaration.line)
+                .cmp(&(&b.declaration.name, b.declaration.line))
+        });
+
+        Self { symbols }
+    }
+
+    pub fn symbols(&self) -> &[GmlSymbol] {
+        &self.symbols
+    }
+
+    /// Every declaration of a name. There's usually one, but a name can be declared more
+    /// than once, such as a macro in several configurations.
+    pub fn find(&self, name: &str) -> impl Iterator<Item = &GmlSymbol> + '_ {
+        let start = self
+            .symbols
+            .partition_point(|v| v.declaration.name.as_str() < name);
+        let name = name.to_string();
+
+        self.symbols[start..]
+            .iter()
+            .take_while(move |v| v.declaration.name == name)
+    }
+
+    /// Every declaration whose name starts with the prefix, such as for autocompletion.
+    pub fn with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a GmlSymbol> + 'a {
+        let start = self
+            .symbols
+            .partition_point(|v| v.declaration.name.as_str() < prefix);
+
+        self.symbols[start..]
+            .iter()
+            .take_while(move |v| v.declaration.name.starts_with(prefix))
+    }
+}
+
+/// Finds the functions, macros, enums and globalvars declared in some GML. Functions are
+/// only found outside of braces, since functions inside of other functions aren't global.
+pub fn scan_declarations(gml: &str) -> Vec<GmlDeclaration> {
+    let tokens = tokenize(gml);
+    let mut output = vec![];
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+
+        match (token.kind, token.text) {
+            (TokenKind::Punctuation, "{") => depth += 1,
+            (TokenKind::Punctuation, "}") => depth = depth.saturating_sub(1),
+            (TokenKind::Directive, text) => {
+                if let Some(declaration) = scan_macro(token, text) {
+                    output.push(declaration);
+                }
+            }
+            (TokenKind::Identifier, "function") if depth == 0 => {
+                if let Some((declaration, next)) = scan_function(&tokens, i) {
+                    output.push(declaration);
+                    i = next;
+                }
+            }
+            (TokenKind::Identifier, "enum") => {
+                if let Some((declaration, next)) = scan_enum(&tokens, i) {
+                    output.push(declaration);
+                    i = next;
+                }
+            }
+            (TokenKind::Identifier, "globalvar") => {
+                while let Some(name) = tokens.get(i).filter(|v| v.kind == TokenKind::Identifier) {
+                    output.push(declaration(name, GmlDeclarationKind::GlobalVar));
+                    i += 1;
+
+                    if tokens.get(i).is_some_and(|v| v.text == ",") {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    output
+}
+
+fn declaration(name: &Token<'_>, kind: GmlDeclarationKind) -> GmlDeclaration {
+    GmlDeclaration {
+        name: name.text.to_string(),
+        kind,
+        line: name.line,
+        column: name.column,
+    }
+}
+
+/// Scans `name(parameters) [: Parent(...)] [constructor]`, starting just after `function`.
+fn scan_function(tokens: &[Token<'_>], mut i: usize) -> Option<(GmlDeclaration, usize)> {
+    let name = tokens.get(i).filter(|v| v.kind == TokenKind::Identifier)?;
+    i += 1;
+    if tokens.get(i)?.text != "(" {
+        return None;
+    }
+    i += 1;
+
+    // the first name in each comma separated part is the parameter -- the rest is its default
+    let mut parameters = vec![];
+    let mut depth = 0usize;
+    let mut expecting_parameter = true;
+    while let Some(token) = tokens.get(i) {
+        i += 1;
+        match token.text {
+            "(" | "[" | "{" => depth += 1,
+            ")" if depth == 0 => break,
+            // a stray bracket ends the parameters of a broken function
+            "]" | "}" if depth == 0 => {
+                i -= 1;
+                break;
+            }
+            ")" | "]" | "}" => depth -= 1,
+            "," if depth == 0 => expecting_parameter = true,
+            _ if expecting_parameter && token.kind == TokenKind::Identifier => {
+                parameters.push(token.text.to_string());
+                expecting_parameter = false;
+            }
+            _ => expecting_parameter = false,
+        }
+    }
+
+    // anything before the body is inheritance, and maybe `constructor`. A function without a
+    // body ends at a `;`, or at a `function` starting the next line
+    let mut constructor = false;
+    let mut j = i;
+    while let Some(token) = tokens.get(j) {
+        match token.text {
+            "{" | ";" => break,
+            "function" if tokens[j - 1].line < token.line => break,
+            "constructor" => constructor = true,
+            _ => {}
+        }
+        j += 1;
+    }
+
+    Some((
+        declaration(
+            name,
+            GmlDeclarationKind::Function {
+                parameters,
+                constructor,
+            },
+        ),
+        i,
+    ))
+}
+
+/// Scans `Name { A, B = 2, C }`, starting just after `enum`.
+fn scan_enum(tokens: &[Token<'_>], mut i: usize) -> Option<(GmlDeclaration, usize)> {
+    let name = tokens.get(i).filter(|v| v.kind == TokenKind::Identifier)?;
+    i += 1;
+    if tokens.get(i)?.text != "{" {
+        return None;
+    }
+    i += 1;
+
+    let mut members = vec![];
+    let mut expecting_member = true;
+    while let Some(token) = tokens.get(i) {
+        i += 1;
+        match token.text {
+            "}" => break,
+            "," => expecting_member = true,
+            _ if expecting_member && token.kind == TokenKind::Identifier => {
+                members.push(token.text.to_string());
+                expecting_member = false;
+            }
+            _ => {}
+        }
+    }
+
+    Some((declaration(name, GmlDeclarationKind::Enum { members }), i))
+}
+
+/// Scans `#macro [Configuration:]NAME value`.
+fn scan_macro(token: Token<'_>, text: &str) -> Option<GmlDeclaration> {
+    let rest = text.strip_prefix("#macro")?;
+    if rest.starts_with(char::is_whitespace) == false {
+        return None;
+    }
+
+    let leading = rest.len() - rest.trim_start().len();
+    let rest = rest.trim_start();
+    let name_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let (full_name, value) = rest.split_at(name_end);
+
+    let (configuration, name) = match full_name.split_once(':') {
+        Some((configuration, name)) => (Some(configuration.to_string()), name),
+        None => (None, full_name),
+    };
+    if name.is_empty() {
+        return None;
+    }
+
+    let value = value
+        .lines()
+        .map(|v| v.trim().trim_end_matches('\\').trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string();
+
+    Some(GmlDeclaration {
+        name: name.to_string(),
+        kind: GmlDeclarationKind::Macro {
+            value,
+            configuration,
+        },
+        line: token.line,
+        column: token.column + "#macro".len() + leading + (full_name.len() - name.len()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declarations() {
+        let gml = r#"
+#macro TILE_SIZE 16
+#macro Debug:LOGGING true
+globalvar score, lives;
+
+enum Direction { Left, Right = 2, Up }
+
+function move(x, y = 1 + f(2, 3)) {
+    function helper() {}
+    enum Inner { A }
+}
+
+function Vector(x, y) : Base(x) constructor {
+    // function not_this() {}
+    var s = "function nor_this() {}";
+}
+"#;
+        let declarations = scan_declarations(gml);
+        let names: Vec<&str> = declarations.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "TILE_SIZE",
+                "LOGGING",
+                "score",
+                "lives",
+                "Direction",
+                "move",
+                "Inner",
+                "Vector"
+            ]
+        );
+
+        assert_eq!(
+            declarations[1].kind,
+            GmlDeclarationKind::Macro {
+                value: "true".to_string(),
+                configuration: Some("Debug".to_string())
+            }
+        );
+        assert_eq!((declarations[1].line, declarations[1].column), (3, 14));
+        assert_eq!(
+            declarations[4].kind,
+            GmlDeclarationKind::Enum {
+                members: vec!["Left".to_string(), "Right".to_string(), "Up".to_string()]
+            }
+        );
+        assert_eq!(
+            declarations[5].kind,
+            GmlDeclarationKind::Function {
+                parameters: vec!["x".to_string(), "y".to_string()],
+                constructor: false
+            }
+        );
+        assert_eq!(
+            declarations[7].kind,
+            GmlDeclarationKind::Function {
+                parameters: vec!["x".to_string(), "y".to_string()],
+                constructor: true
+            }
+        );
+
+        let table = SymbolTable::new([
+            (
+                GmlSource::Script {
+                    name: "scr_move".to_string(),
+                },
+                gml,
+            ),
+            (
+                GmlSource::ObjectEvent {
+                    object: "obj_player".to_string(),
+                    event: "Create_0".to_string(),
+                },
+                "#macro TILE_SIZE 32",
+            ),
+        ]);
+        assert_eq!(table.find("TILE_SIZE").count(), 2);
+        assert_eq!(
+            table
+                .with_prefix("L")
+                .map(|v| v.declaration.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["LOGGING"]
+        );
+
+        // broken functions end where they can, and don't take the next one's `constructor`
+        let broken = scan_declarations(
+            "function foo(a,\n}\nfunction bar(]\nfunction Baz() constructor {}\nfunction qux(); constructor",
+        );
+        let names: Vec<&str> = broken.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["foo", "bar", "Baz", "qux"]);
+        let constructors: Vec<bool> = broken
+            .iter()
+            .map(|v| {
+                matches!(
+                    v.kind,
+                    GmlDeclarationKind::Function {
+                        constructor: true,
+                        ..
+                    }
+                )
+            })
+            .collect();
+        assert_eq!(constructors, vec![false, false, true, false]);
+    }
+}
@@ -0,0 +1,123 @@
+use super::{
+    lexer::{tokenize, Token, TokenKind},
+    GmlSource,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Where a name is used in a project's GML.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmlUsage {
+    pub source: GmlSource,
+
+    /// The GML file, relative to the project's root directory.
+    pub path: PathBuf,
+
+    #[serde(flatten)]
+    pub position: GmlPosition,
+}
+
+/// A position in some GML. Both start at 1, and columns count characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct GmlPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Finds every place a name is used as an identifier, such as `spr_player` in
+/// `sprite_index = spr_player`. Names inside strings or comments, inside longer names, or
+/// after a `.` (such as `other.spr_player`) aren't counted. Names in `#macro` values are.
+pub fn find_identifier(gml: &str, name: &str) -> Vec<GmlPosition> {
+    identifier_tokens(gml, name)
+        .into_iter()
+        .map(|v| GmlPosition {
+            line: v.line,
+            column: v.column,
+        })
+        .collect()
+}
+
+/// Replaces every use of a name found by [`find_identifier`] with another name.
+pub fn rename_identifier(gml: &str, name: &str, new_name: &str) -> String {
+    let mut output = String::with_capacity(gml.len());
+    let mut last = 0;
+
+    for token in identifier_tokens(gml, name) {
+        output.push_str(&gml[last..token.offset]);
+        output.push_str(new_name);
+        last = token.offset + token.text.len();
+    }
+    output.push_str(&gml[last..]);
+
+    output
+}
+
+fn identifier_tokens<'a>(gml: &'a str, name: &str) -> Vec<Token<'a>> {
+    let mut output = vec![];
+    let mut previous: Option<Token<'_>> = None;
+
+    for token in tokenize(gml) {
+        match token.kind {
+            TokenKind::Identifier
+                if token.text == name && previous.is_none_or(|v| v.text != ".") =>
+            {
+                output.push(token);
+            }
+            TokenKind::Directive if token.text.starts_with("#macro") => {
+                // we look inside macros by lexing them again, without the `#`
+                let inner = &token.text[1..];
+                for inner_token in identifier_tokens(inner, name) {
+                    output.push(Token {
+                        offset: token.offset + 1 + inner_token.offset,
+                        line: token.line + inner_token.line - 1,
+                        column: if inner_token.line == 1 {
+                            token.column + inner_token.column
+                        } else {
+                            inner_token.column
+                        },
+                        ..inner_token
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        previous = Some(token);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usages() {
+        let gml = "#macro PLAYER_SPRITE spr_player\nsprite_index = spr_player; // spr_player\nvar s = \"spr_player\" + spr_player_big + other.spr_player;\ndraw_sprite(spr_player, 0, x, y);";
+
+        assert_eq!(
+            find_identifier(gml, "spr_player"),
+            vec![
+                GmlPosition {
+                    line: 1,
+                    column: 22
+                },
+                GmlPosition {
+                    line: 2,
+                    column: 16
+                },
+                GmlPosition {
+                    line: 4,
+                    column: 13
+                },
+            ]
+        );
+
+        assert_eq!(
+            rename_identifier(gml, "spr_player", "spr_hero"),
+            "#macro PLAYER_SPRITE spr_hero\nsprite_index = spr_hero; // spr_player\nvar s = \"spr_player\" + spr_player_big + other.spr_player;\ndraw_sprite(spr_hero, 0, x, y);"
+        );
+    }
+}
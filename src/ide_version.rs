@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// A GameMaker IDE version, such as `2024.8.1.171`.
+///
+/// GameMaker versions are a year followed by a semver-like triple, so we store them
+/// as exactly that. Versions are ordered by year first, and then by the rest of the version.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct IdeVersion {
+    pub year: u32,
+    pub version: semver::Version,
+}
+
+impl IdeVersion {
+    pub fn new(year: u32, major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            year,
+            version: semver::Version::new(major, minor, patch),
+        }
+    }
+
+    /// Parses a version string as found in a yyp's `ideVersion` field.
+    pub fn parse(input: &str) -> Result<Self, IdeVersionError> {
+        let (year, version) = input
+            .split_once('.')
+            .ok_or_else(|| IdeVersionError::MissingYear(input.to_string()))?;
+
+        let year = year
+            .parse()
+            .map_err(|_| IdeVersionError::BadYear(input.to_string()))?;
+
+        let version = semver::Version::parse(version).map_err(|e| IdeVersionError::BadVersion {
+            input: input.to_string(),
+            error: e.to_string(),
+        })?;
+
+        Ok(Self { year, version })
+    }
+
+    /// The version which this crate was built against, which is `Yyp::DEFAULT_VERSION`.
+    pub fn supported() -> Self {
+        Self::parse(yy_typings::Yyp::DEFAULT_VERSION).expect("yy-typings version is well formed")
+    }
+
+    /// The requirement on the non-year portion of the version which a strict load uses.
+    pub(crate) fn requirement(&self) -> semver::VersionReq {
+        semver::VersionReq {
+            comparators: vec![semver::Comparator {
+                op: semver::Op::Caret,
+                major: self.version.major,
+                minor: Some(self.version.minor),
+                patch: Some(self.version.patch),
+                pre: self.version.pre.clone(),
+            }],
+        }
+    }
+}
+
+impl FromStr for IdeVersion {
+    type Err = IdeVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for IdeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.year, self.version)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum IdeVersionError {
+    #[error("version {0:?} has no year component")]
+    MissingYear(String),
+
+    #[error("version {0:?} has a year which is not a number")]
+    BadYear(String),
+
+    #[error("version {input:?} is not a valid version -- {error}")]
+    BadVersion { input: String, error: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            IdeVersion::parse("2024.8.1.171").unwrap(),
+            IdeVersion::new(2024, 8, 1, 171)
+        );
+        assert_eq!(IdeVersion::new(2024, 8, 1, 171).to_string(), "2024.8.1.171");
+
+        assert!(matches!(
+            IdeVersion::parse("2024"),
+            Err(IdeVersionError::MissingYear(_))
+        ));
+        assert!(matches!(
+            IdeVersion::parse("twenty.8.1.171"),
+            Err(IdeVersionError::BadYear(_))
+        ));
+        assert!(matches!(
+            IdeVersion::parse("2024.8.1"),
+            Err(IdeVersionError::BadVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(IdeVersion::new(2023, 11, 0, 0) < IdeVersion::new(2024, 2, 0, 0));
+        assert!(IdeVersion::new(2024, 2, 0, 0) < IdeVersion::new(2024, 8, 1, 171));
+    }
+}
@@ -23,6 +23,16 @@ pub use utils::{FileSerializationError, SerializationFormat};
 mod project_metadata;
 pub use project_metadata::ProjectMetadata;
 
+mod ide_version;
+pub use ide_version::{IdeVersion, IdeVersionError};
+
+mod load_cache;
+mod load_options;
+pub use load_options::{LoadFailure, LoadOptions, LoadReport, VersionPolicy};
+
+mod placeholders;
+pub use placeholders::{Placeholder, PlaceholderHandler};
+
 mod errors;
 pub use errors::*;
 
@@ -32,4 +42,15 @@ pub use folders::*;
 mod resources_ext;
 pub use resources_ext::*;
 
+mod texture_pages;
+pub use texture_pages::{
+    pack_sprites, PackedFrame, PackingReport, PackingSettings, TexturePackingError, TexturePage,
+};
+
+mod duplicate_frames;
+pub use duplicate_frames::{find_duplicates, DuplicateReport, FrameRef, SimilarFrames};
+
+mod gml;
+pub use gml::*;
+
 pub mod cli;
@@ -0,0 +1,236 @@
+use crate::{directory_manager::DirectoryManager, utils, FileSerializationError};
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use yy_typings::TrailingCommaUtility;
+
+/// A cache of parsed yy files, which lets a full load skip reading and parsing files which
+/// have not changed since the previous load. It lives in the boss directory next to the yyp.
+///
+/// Entries are keyed by the yy file's path relative to the root directory, and are only
+/// used if the file's modified time and size are exactly what they were when it was cached.
+/// Only yy files are cached, never associated data.
+///
+/// The cache is a plain text file, with the crate version on the first line and then one
+/// entry per line, as tab separated `path`, `modified secs`, `modified nanos`, `len` and
+/// the yy file as compact json. Compact json has no trailing commas, so a cached file is
+/// deserialized directly, without the trailing comma pass or a read of the yy file itself.
+#[derive(Debug, Default)]
+pub(crate) struct LoadCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CacheEntry {
+    fingerprint: Fingerprint,
+    yy: String,
+}
+
+/// What we check to decide if a file has changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    modified_secs: u64,
+    modified_nanos: u32,
+    len: u64,
+}
+
+impl Fingerprint {
+    fn new(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+        Some(Self {
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            len: metadata.len(),
+        })
+    }
+}
+
+impl LoadCache {
+    const FILE_NAME: &'static str = "load_cache";
+
+    /// The cache is thrown away whenever this crate changes version, since the typings
+    /// we cache may have changed.
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    /// Reads the cache from the boss directory. If there is no cache, or it can't be
+    /// read, or it was made by a different version of this crate, an empty cache is returned.
+    pub(crate) fn load(directory_manager: &DirectoryManager) -> Self {
+        let path = directory_manager.boss_directory().join(Self::FILE_NAME);
+        if path.exists() == false {
+            return Self::default();
+        }
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("couldn't read load cache, ignoring it -- {}", e);
+                return Self::default();
+            }
+        };
+
+        let mut lines = contents.lines();
+        if lines.next() != Some(Self::VERSION) {
+            info!("load cache was made by a different version of yy-boss, ignoring it");
+            return Self::default();
+        }
+
+        match lines.map(Self::parse_line).collect::<Option<_>>() {
+            Some(entries) => Self { entries },
+            None => {
+                warn!("load cache is malformed, ignoring it");
+                Self::default()
+            }
+        }
+    }
+
+    fn parse_line(line: &str) -> Option<(PathBuf, CacheEntry)> {
+        let mut fields = line.splitn(5, '\t');
+        let path = PathBuf::from(fields.next()?);
+        let fingerprint = Fingerprint {
+            modified_secs: fields.next()?.parse().ok()?,
+            modified_nanos: fields.next()?.parse().ok()?,
+            len: fields.next()?.parse().ok()?,
+        };
+        let yy = fields.next()?.to_owned();
+
+        Some((path, CacheEntry { fingerprint, yy }))
+    }
+
+    /// Reads a yy file, using the cached copy if the file hasn't changed. If the file was
+    /// parsed instead, returns the entry which should replace its cached copy.
+    pub(crate) fn read<T: Serialize + DeserializeOwned>(
+        &self,
+        relative_path: &Path,
+        absolute_path: &Path,
+        tcu: &TrailingCommaUtility,
+    ) -> Result<(T, Option<CacheEntry>), FileSerializationError> {
+        let fingerprint = Fingerprint::new(absolute_path);
+
+        let cached = self
+            .entries
+            .get(relative_path)
+            .filter(|entry| Some(entry.fingerprint) == fingerprint)
+            .and_then(|entry| serde_json::from_str(&entry.yy).ok());
+
+        if let Some(yy) = cached {
+            return Ok((yy, None));
+        }
+
+        let yy: T = utils::deserialize_json_tc(absolute_path, tcu)?;
+        let entry = fingerprint.and_then(|fingerprint| {
+            serde_json::to_string(&yy)
+                .ok()
+                .map(|yy| CacheEntry { fingerprint, yy })
+        });
+
+        Ok((yy, entry))
+    }
+
+    /// Brings the cache up to date after a load, and writes it to the boss directory if
+    /// anything changed. Files which weren't loaded this time are dropped.
+    ///
+    /// Failing to write the cache only costs us speed on the next load, so errors are
+    /// logged rather than returned.
+    pub(crate) fn update(
+        mut self,
+        directory_manager: &DirectoryManager,
+        loaded: HashSet<&Path>,
+        new_entries: Vec<(PathBuf, CacheEntry)>,
+    ) {
+        let len = self.entries.len();
+        self.entries
+            .retain(|path, _| loaded.contains(path.as_path()));
+
+        if new_entries.is_empty() && self.entries.len() == len {
+            return;
+        }
+        self.entries.extend(new_entries);
+
+        let directory = directory_manager.boss_directory();
+        if let Err(e) = fs::create_dir_all(&directory) {
+            warn!("couldn't create boss directory {:?} -- {}", directory, e);
+            return;
+        }
+
+        let mut output = format!("{}\n", Self::VERSION);
+        for (path, entry) in &self.entries {
+            let Some(path) = path.to_str().filter(|p| p.contains(['\t', '\n']) == false) else {
+                continue;
+            };
+            let fingerprint = entry.fingerprint;
+
+            output.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                path,
+                fingerprint.modified_secs,
+                fingerprint.modified_nanos,
+                fingerprint.len,
+                entry.yy
+            ));
+        }
+
+        let path = directory.join(Self::FILE_NAME);
+        if let Err(e) = fs::write(&path, output) {
+            warn!("couldn't write load cache to {:?} -- {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Yy {
+        name: String,
+    }
+
+    #[test]
+    fn read_uses_unchanged_entries() {
+        let dir = std::env::temp_dir().join(format!("yy-boss-load-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let absolute_path = dir.join("thing.yy");
+        fs::write(&absolute_path, r#"{"name": "spr_player",}"#).unwrap();
+
+        let tcu = TrailingCommaUtility::new();
+        let mut cache = LoadCache::default();
+        let relative_path = Path::new("sprites/thing.yy");
+
+        let (yy, entry): (Yy, _) = cache.read(relative_path, &absolute_path, &tcu).unwrap();
+        assert_eq!(yy.name, "spr_player");
+
+        // poison the cached copy so we can tell if it was used...
+        let mut entry = entry.unwrap();
+        assert_eq!(entry.yy, r#"{"name":"spr_player"}"#);
+        entry.yy = r#"{"name":"from_cache"}"#.to_owned();
+        cache
+            .entries
+            .insert(relative_path.to_owned(), entry.clone());
+
+        let line = format!("sprites/thing.yy\t1\t2\t3\t{}", entry.yy);
+        assert_eq!(
+            LoadCache::parse_line(&line).unwrap().1.yy,
+            r#"{"name":"from_cache"}"#
+        );
+
+        let (yy, new_entry): (Yy, _) = cache.read(relative_path, &absolute_path, &tcu).unwrap();
+        assert_eq!(yy.name, "from_cache");
+        assert!(new_entry.is_none());
+
+        // ...and a changed file is read again.
+        fs::write(&absolute_path, r#"{"name": "spr_enemy"}"#).unwrap();
+        let (yy, new_entry): (Yy, _) = cache.read(relative_path, &absolute_path, &tcu).unwrap();
+        assert_eq!(yy.name, "spr_enemy");
+        assert!(new_entry.is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
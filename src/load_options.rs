@@ -0,0 +1,80 @@
+use crate::{IdeVersion, Resource, StartupError};
+use std::path::PathBuf;
+
+/// Options for loading a project with [`YypBoss::with_options`].
+///
+/// The default options are strict, and load no associated data.
+///
+/// [`YypBoss::with_options`]: crate::YypBoss::with_options
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LoadOptions {
+    /// The resources which will have their associated data loaded on startup. Associated data
+    /// for every other resource is loaded lazily.
+    pub associated_data: Vec<Resource>,
+
+    /// If `true`, a resource which fails to load will not abort startup. Instead, the error
+    /// is collected into the [`LoadReport`], and the resource is kept in the Vfs as a
+    /// read-only [`Placeholder`], which can still be moved or removed.
+    ///
+    /// If associated data fails to load, the resource is loaded without it, and the error
+    /// is reported.
+    ///
+    /// [`Placeholder`]: crate::Placeholder
+    pub lenient: bool,
+
+    /// If `true`, parsed yy files are cached in a hidden `.yyboss` directory next to the yyp,
+    /// and on the next load with caching, only yy files whose modified time or size changed
+    /// are read and parsed again. Associated data is never cached.
+    pub cache: bool,
+
+    /// How strictly the yyp's IDE version is checked against the version this crate supports.
+    pub version_policy: VersionPolicy,
+}
+
+/// The policy for handling a yyp made by a different version of the IDE than
+/// the one this crate supports, which is [`IdeVersion::supported`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum VersionPolicy {
+    /// The yyp must have the same year as the supported version, and a version compatible
+    /// with it (by semver caret rules). Anything else, including a malformed version, is an error.
+    #[default]
+    Strict,
+
+    /// Any version is loaded, but a mismatched or malformed version is logged as a warning.
+    Warn,
+
+    /// Any well-formed version within `min..=max` is loaded.
+    AcceptRange { min: IdeVersion, max: IdeVersion },
+}
+
+/// A report of every resource which failed to load when loading leniently.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub failures: Vec<LoadFailure>,
+}
+
+impl LoadReport {
+    /// Returns `true` if every resource loaded successfully.
+    pub fn is_clean(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// A single resource which failed to load.
+#[derive(Debug)]
+pub struct LoadFailure {
+    /// The name of the resource, as listed in the yyp.
+    pub name: String,
+
+    /// The path to the yy file, relative to the root directory, as listed in the yyp.
+    pub yy_path: PathBuf,
+
+    /// The kind of resource, if it could be determined.
+    pub resource: Option<Resource>,
+
+    /// If the resource was loaded into the Vfs as a placeholder. This is `false` if the
+    /// resource only failed to load its associated data, or if its kind couldn't be determined.
+    pub placeholder: bool,
+
+    pub error: StartupError,
+}
@@ -0,0 +1,137 @@
+use crate::{
+    directory_manager::DirectoryManager, Resource, ResourceManipulationError, SerializationFailure,
+};
+use log::{error, info};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+use yy_typings::ViewPath;
+
+/// A resource which could not be loaded when the project was loaded leniently.
+///
+/// Placeholders are read-only: they are tracked in the Vfs so that they can still be moved
+/// or removed, but their yy file is otherwise left exactly as it was found on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    /// The name of the resource, as listed in the yyp.
+    pub name: String,
+
+    /// The kind of resource, as inferred from its path in the yyp.
+    pub resource: Resource,
+
+    /// The path to the yy file, relative to the root directory, such as
+    /// `sprites/spr_player/spr_player.yy`.
+    pub yy_path: PathBuf,
+
+    /// The parent of the resource in the Vfs.
+    pub parent: ViewPath,
+
+    /// The reason this resource could not be loaded.
+    pub error: String,
+
+    /// The yy file as raw json, if it could be read as json at all. A placeholder without
+    /// raw json can be removed, but it cannot be moved, since we cannot rewrite its parent.
+    pub raw: Option<serde_json::Value>,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct PlaceholderHandler {
+    placeholders: HashMap<String, Placeholder>,
+    edited: HashSet<String>,
+    removed: Vec<Placeholder>,
+}
+
+impl PlaceholderHandler {
+    /// Returns the placeholder of the given name, if there is one.
+    pub fn get(&self, name: &str) -> Option<&Placeholder> {
+        self.placeholders.get(name)
+    }
+
+    /// Get a reference to all of the placeholders in the project.
+    pub fn placeholders(&self) -> &HashMap<String, Placeholder> {
+        &self.placeholders
+    }
+
+    /// Loads the placeholder in on startup. We don't mark the placeholder as dirty.
+    pub(crate) fn load_on_startup(&mut self, placeholder: Placeholder) {
+        self.placeholders
+            .insert(placeholder.name.clone(), placeholder);
+    }
+
+    pub(crate) fn edit_parent(
+        &mut self,
+        name: &str,
+        parent: ViewPath,
+    ) -> Result<(), ResourceManipulationError> {
+        let placeholder = self
+            .placeholders
+            .get_mut(name)
+            .ok_or(ResourceManipulationError::BadGet)?;
+
+        let raw = placeholder
+            .raw
+            .as_mut()
+            .and_then(|v| v.as_object_mut())
+            .ok_or(ResourceManipulationError::ResourceCannotBeManipulated)?;
+
+        raw.insert(
+            "parent".to_string(),
+            serde_json::to_value(&parent).map_err(|_| ResourceManipulationError::InternalError)?,
+        );
+        placeholder.parent = parent;
+        self.edited.insert(name.to_string());
+
+        Ok(())
+    }
+
+    /// Removes the placeholder. Its directory will be removed on the next serialization.
+    pub(crate) fn remove(&mut self, name: &str) -> Option<Placeholder> {
+        let placeholder = self.placeholders.remove(name)?;
+        self.edited.remove(name);
+        self.removed.push(placeholder.clone());
+
+        Some(placeholder)
+    }
+
+    /// Removes the directories of removed placeholders, and rewrites the yy files of
+    /// placeholders which were moved, returning every placeholder which couldn't be written.
+    pub(crate) fn serialize(
+        &mut self,
+        directory_manager: &DirectoryManager,
+    ) -> Vec<SerializationFailure> {
+        for placeholder in self.removed.drain(..) {
+            let yy_path = directory_manager.resource_file(&placeholder.yy_path);
+            let Some(dir) = yy_path.parent() else {
+                error!("placeholder {} had no parent directory", placeholder.name);
+                continue;
+            };
+
+            match fs::remove_dir_all(dir) {
+                Ok(()) => info!("removed placeholder folder {:?}", dir),
+                Err(e) => error!("couldn't remove placeholder folder {:#?}, {:#?}", dir, e),
+            }
+        }
+
+        // placeholders which couldn't be written stay marked, so the next serialization
+        // retries them
+        let mut failures = vec![];
+        for name in std::mem::take(&mut self.edited) {
+            let placeholder = &self.placeholders[&name];
+            if let Some(raw) = &placeholder.raw {
+                let yy_path = directory_manager.resource_file(&placeholder.yy_path);
+                if let Err(e) = fs::write(yy_path, yy_typings::serialize_file(raw)) {
+                    failures.push(SerializationFailure {
+                        name: name.clone(),
+                        resource: placeholder.resource,
+                        error: e.into(),
+                    });
+                    self.edited.insert(name);
+                }
+            }
+        }
+
+        failures
+    }
+}
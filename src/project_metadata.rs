@@ -1,3 +1,4 @@
+use crate::IdeVersion;
 use serde::{Deserialize, Serialize};
 use yy_typings::{ResourceVersion, ViewPath};
 
@@ -6,6 +7,9 @@ use yy_typings::{ResourceVersion, ViewPath};
 pub struct ProjectMetadata {
     pub name: String,
     pub ide_version: String,
+    /// The parsed `ide_version`, or `None` if it was malformed.
+    #[serde(default)]
+    pub detected_version: Option<IdeVersion>,
     pub yyp_version: ResourceVersion,
     pub root_file: ViewPath,
 }
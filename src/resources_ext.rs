@@ -3,10 +3,42 @@ use super::*;
 mod sprite_ext;
 pub use sprite_ext::*;
 
+mod sprite_images;
+pub use sprite_images::*;
+
+mod sprite_collision;
+pub use sprite_collision::*;
+
+mod sprite_sheet;
+pub use sprite_sheet::*;
+
+mod sprite_nine_slice;
+pub use sprite_nine_slice::*;
+
+mod sprite_export;
+pub use sprite_export::*;
+
+mod sprite_canvas;
+pub use sprite_canvas::*;
+
+mod aseprite;
+pub use aseprite::*;
+
 mod paths_ext;
 pub use paths_ext::*;
 
 mod object_ext;
+pub use object_ext::*;
+
+mod object_properties;
+pub use object_properties::*;
+
+mod object_physics;
+pub use object_physics::*;
+
+mod object_events;
+pub use object_events::*;
+
 mod script_ext;
 
 pub(crate) mod dummy;
@@ -16,6 +48,9 @@ mod note_ext;
 mod shader_ext;
 pub use shader_ext::*;
 
+mod shader_language;
+pub use shader_language::*;
+
 mod sound_ext;
 
 mod tile_set_ext;
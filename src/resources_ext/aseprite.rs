@@ -0,0 +1,1019 @@
+use super::{SpriteExt, SpriteImageBuffer, SpriteImages};
+use image::Rgba;
+use log::warn;
+use std::{num::NonZeroUsize, path::Path};
+use thiserror::Error;
+use yy_typings::{
+    CommonData, FrameId, LayerId, PlaybackSpeed, Sprite, SpriteLayer, SpriteSequenceId,
+    TexturePath, ViewPath,
+};
+
+/// A parsed `.aseprite` (or `.ase`) file. Only what we need to make a sprite is kept --
+/// tilemaps, slices and user data are skipped.
+///
+/// Layers are in Aseprite's order, which is bottom to top, and linked cels are resolved
+/// into copies of the cel they link to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsepriteFile {
+    pub width: u16,
+    pub height: u16,
+    pub layers: Vec<AsepriteLayer>,
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsepriteLayer {
+    pub name: String,
+    pub kind: AsepriteLayerKind,
+
+    /// Whether the layer itself is visible. A layer in a hidden group is hidden even if
+    /// this is true.
+    pub visible: bool,
+    pub background: bool,
+
+    /// How deeply nested the layer is in groups. A layer belongs to the closest group
+    /// above it with a lower child level.
+    pub child_level: u16,
+
+    /// Aseprite's blend mode number. See [`AsepriteLayer::blend_mode`].
+    pub blend_mode: u16,
+    pub opacity: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsepriteLayerKind {
+    Image,
+    Group,
+    Tilemap,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsepriteFrame {
+    /// How long the frame is shown for, in milliseconds.
+    pub duration: u16,
+    pub cels: Vec<AsepriteCel>,
+}
+
+/// The image of a single layer in a single frame. Cels can be smaller than the canvas,
+/// and can hang off of its edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsepriteCel {
+    pub layer: usize,
+    pub position: (i16, i16),
+    pub opacity: u8,
+    pub image: SpriteImageBuffer,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsepriteTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: AsepriteDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsepriteDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    /// Plays the frames backwards, then forwards.
+    PingPongReverse,
+}
+
+/// What to do with an Aseprite file's tags when making sprites out of it.
+///
+/// Mapping tags to broadcast messages is out of scope, like the rest of broadcast message
+/// support in [`SpriteExt::set_frame_length`]: `yy-typings` types the keyframes of a
+/// sprite's events as `Vec<()>`, so a message can't be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AsepriteTags {
+    /// Make a single sprite out of every frame, ignoring tags.
+    #[default]
+    Ignore,
+
+    /// Make a sprite for each tag, named `<name>_<tag>`, out of the tag's frames in the
+    /// order the tag plays them. A file with no tags becomes a single sprite.
+    SeparateSprites,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum AsepriteError {
+    #[error("couldn't read aseprite file -- {0}")]
+    Io(String),
+
+    #[error("not an aseprite file -- bad magic number {0:#06x}")]
+    BadMagic(u16),
+
+    #[error("frame {frame} has bad magic number {magic:#06x}")]
+    BadFrameMagic { frame: usize, magic: u16 },
+
+    #[error("color depth of {0} bits per pixel is not supported")]
+    UnsupportedColorDepth(u16),
+
+    #[error("aseprite file ended unexpectedly")]
+    UnexpectedEof,
+
+    #[error("couldn't decompress a cel in frame {frame} -- {error}")]
+    BadCompression { frame: usize, error: String },
+
+    #[error("a cel in frame {frame} is on layer {layer}, which doesn't exist")]
+    MissingLayer { frame: usize, layer: usize },
+
+    #[error("a cel in frame {frame} links to frame {link}, which has no cel on that layer")]
+    BadLink { frame: usize, link: usize },
+
+    #[error("aseprite file has an empty canvas, no frames or no image layers")]
+    Empty,
+
+    #[error("tag {0:?} has frames which don't exist")]
+    BadTag(String),
+
+    #[error(
+        "palette sets colors {first} to {last}, which don't fit its size of {size} or its chunk"
+    )]
+    BadPalette {
+        first: usize,
+        last: usize,
+        size: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorDepth {
+    Rgba,
+    Grayscale,
+    Indexed,
+}
+
+impl ColorDepth {
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            ColorDepth::Rgba => 4,
+            ColorDepth::Grayscale => 2,
+            ColorDepth::Indexed => 1,
+        }
+    }
+}
+
+/// A cel's pixels before they're turned into an image, since indexed images need the
+/// palette, which can come after the cel.
+enum RawCel {
+    Pixels {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+    Linked(usize),
+}
+
+struct PendingCel {
+    frame: usize,
+    layer: usize,
+    position: (i16, i16),
+    opacity: u8,
+    raw: RawCel,
+}
+
+impl AsepriteFile {
+    const MAGIC: u16 = 0xA5E0;
+    const FRAME_MAGIC: u16 = 0xF1FA;
+    const HEADER_SIZE: usize = 128;
+    const FRAME_HEADER_SIZE: usize = 16;
+
+    /// Reads and parses an Aseprite file.
+    pub fn open(path: &Path) -> Result<Self, AsepriteError> {
+        let data = std::fs::read(path).map_err(|e| AsepriteError::Io(e.to_string()))?;
+        Self::parse(&data)
+    }
+
+    /// Parses the bytes of an Aseprite file.
+    pub fn parse(data: &[u8]) -> Result<Self, AsepriteError> {
+        let mut header = Reader::new(data);
+        header.skip(4)?; // file size
+        let magic = header.u16()?;
+        if magic != Self::MAGIC {
+            return Err(AsepriteError::BadMagic(magic));
+        }
+        let frame_count = header.u16()? as usize;
+        let width = header.u16()?;
+        let height = header.u16()?;
+        let color_depth = match header.u16()? {
+            32 => ColorDepth::Rgba,
+            16 => ColorDepth::Grayscale,
+            8 => ColorDepth::Indexed,
+            other => return Err(AsepriteError::UnsupportedColorDepth(other)),
+        };
+        let layer_opacity_valid = header.u32()? & 1 == 1;
+        header.skip(2 + 4 + 4)?; // speed, which is deprecated, and two reserved fields
+        let transparent_index = header.u8()?;
+
+        let mut layers = vec![];
+        let mut frames = Vec::with_capacity(frame_count);
+        let mut tags = vec![];
+        let mut palette = vec![];
+        let mut pending_cels = vec![];
+
+        let mut position = Self::HEADER_SIZE;
+        for frame in 0..frame_count {
+            let mut frame_header = Reader::new(data.get(position..).unwrap_or_default());
+            let frame_size = frame_header.u32()? as usize;
+            let magic = frame_header.u16()?;
+            if magic != Self::FRAME_MAGIC {
+                return Err(AsepriteError::BadFrameMagic { frame, magic });
+            }
+            let old_chunk_count = frame_header.u16()? as usize;
+            let duration = frame_header.u16()?;
+            frame_header.skip(2)?;
+            let chunk_count = match frame_header.u32()? as usize {
+                0 => old_chunk_count,
+                new => new,
+            };
+
+            let frame_data = data
+                .get(position..position + frame_size)
+                .ok_or(AsepriteError::UnexpectedEof)?;
+            let mut chunks = Reader::new(frame_data);
+            chunks.skip(Self::FRAME_HEADER_SIZE)?;
+
+            for _ in 0..chunk_count {
+                let chunk_size = chunks.u32()? as usize;
+                let chunk_type = chunks.u16()?;
+                let mut chunk = Reader::new(chunks.bytes(chunk_size.saturating_sub(6))?);
+
+                match chunk_type {
+                    // old palettes are only used if there's no new palette
+                    0x0004 | 0x0011 if palette.is_empty() => {
+                        palette = parse_old_palette(&mut chunk, chunk_type == 0x0011)?;
+                    }
+                    0x2004 => layers.push(parse_layer(&mut chunk, layer_opacity_valid)?),
+                    0x2005 => {
+                        if let Some(cel) = parse_cel(&mut chunk, frame, color_depth)? {
+                            pending_cels.push(cel);
+                        }
+                    }
+                    0x2018 => tags = parse_tags(&mut chunk)?,
+                    0x2019 => parse_palette(&mut chunk, &mut palette)?,
+                    _ => {}
+                }
+            }
+
+            frames.push(AsepriteFrame {
+                duration,
+                cels: vec![],
+            });
+            position += frame_size;
+        }
+
+        for cel in pending_cels {
+            let background = layers
+                .get(cel.layer)
+                .ok_or(AsepriteError::MissingLayer {
+                    frame: cel.frame,
+                    layer: cel.layer,
+                })?
+                .background;
+
+            let image = match cel.raw {
+                RawCel::Pixels {
+                    width,
+                    height,
+                    pixels,
+                } => {
+                    let transparent_index = if background {
+                        None
+                    } else {
+                        Some(transparent_index)
+                    };
+                    to_image(
+                        width,
+                        height,
+                        &pixels,
+                        color_depth,
+                        &palette,
+                        transparent_index,
+                    )?
+                }
+                RawCel::Linked(link) => frames
+                    .get(link)
+                    .and_then(|f| f.cels.iter().find(|v| v.layer == cel.layer))
+                    .map(|v| v.image.clone())
+                    .ok_or(AsepriteError::BadLink {
+                        frame: cel.frame,
+                        link,
+                    })?,
+            };
+
+            frames[cel.frame].cels.push(AsepriteCel {
+                layer: cel.layer,
+                position: cel.position,
+                opacity: cel.opacity,
+                image,
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            layers,
+            frames,
+            tags,
+        })
+    }
+
+    /// Makes sprites out of the file. Every image layer becomes a layer of the sprite, with
+    /// groups flattened away, and frame durations become the lengths of the frames' keyframes.
+    /// The sprites get an automatic bbox, and are otherwise the same as [`SpriteExt::new`].
+    pub fn to_sprites(
+        &self,
+        name: &str,
+        texture_group_id: TexturePath,
+        parent: ViewPath,
+        tags: AsepriteTags,
+    ) -> Result<Vec<(Sprite, SpriteImages)>, AsepriteError> {
+        let all_frames: Vec<usize> = (0..self.frames.len()).collect();
+
+        if tags == AsepriteTags::Ignore || self.tags.is_empty() {
+            return Ok(vec![self.to_sprite(
+                name,
+                texture_group_id,
+                parent,
+                &all_frames,
+            )?]);
+        }
+
+        self.tags
+            .iter()
+            .map(|tag| {
+                let frames = tag.frames(self.frames.len())?;
+                let name = format!("{}_{}", name, sanitize_tag_name(&tag.name));
+
+                self.to_sprite(&name, texture_group_id.clone(), parent.clone(), &frames)
+            })
+            .collect()
+    }
+
+    /// Makes a single sprite out of the given frames, which may repeat.
+    fn to_sprite(
+        &self,
+        name: &str,
+        texture_group_id: TexturePath,
+        parent: ViewPath,
+        frames: &[usize],
+    ) -> Result<(Sprite, SpriteImages), AsepriteError> {
+        let width = NonZeroUsize::new(self.width as usize).ok_or(AsepriteError::Empty)?;
+        let height = NonZeroUsize::new(self.height as usize).ok_or(AsepriteError::Empty)?;
+
+        // gamemaker's layers are top to bottom, so we go backwards
+        let layers: Vec<(usize, SpriteLayer)> = self
+            .image_layers()
+            .into_iter()
+            .rev()
+            .map(|(index, visible)| (index, self.layers[index].to_sprite_layer(visible)))
+            .collect();
+        if layers.is_empty() || frames.is_empty() {
+            return Err(AsepriteError::Empty);
+        }
+
+        let mut sprite = Sprite::with_layer(name, texture_group_id, layers[0].1.clone(), parent)
+            .dimensions(width, height);
+        for (_, layer) in layers.iter().skip(1) {
+            sprite.set_add_layer(sprite.layers.len(), layer.clone());
+        }
+
+        let mut images = SpriteImages::default();
+        for frame_index in frames {
+            let frame = &self.frames[*frame_index];
+            let frame_id = FrameId::new();
+            sprite.set_frame(frame_id, SpriteSequenceId::new());
+
+            for (aseprite_layer, layer) in layers.iter() {
+                let mut image = SpriteImageBuffer::new(self.width as u32, self.height as u32);
+                for cel in frame.cels.iter().filter(|v| v.layer == *aseprite_layer) {
+                    draw_cel(&mut image, cel);
+                }
+
+                images.set_layer_image(frame_id, layer.common_data.name, image);
+            }
+        }
+
+        // every duration is a whole number of ticks of the sequence
+        let tick = frames
+            .iter()
+            .map(|v| self.frames[*v].duration.max(1))
+            .fold(0, gcd);
+        sprite.sequence.playback_speed_type = PlaybackSpeed::FramesPerSecond;
+        sprite.sequence.playback_speed = 1000.0 / tick as f32;
+        for (frame, frame_index) in sprite.frames.clone().iter().zip(frames) {
+            let length = self.frames[*frame_index].duration.max(1) / tick;
+            sprite.set_frame_length(frame.name, length as f64);
+        }
+
+        images.recomposite(&sprite);
+        sprite.set_automatic_bbox(&images);
+
+        Ok((sprite, images))
+    }
+
+    /// The indices of every image layer, bottom to top, and whether they're visible, taking
+    /// the visibility of the groups they're in into account.
+    fn image_layers(&self) -> Vec<(usize, bool)> {
+        let mut groups: Vec<(u16, bool)> = vec![];
+        let mut output = vec![];
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            while groups
+                .last()
+                .is_some_and(|(level, _)| *level >= layer.child_level)
+            {
+                groups.pop();
+            }
+            let visible = layer.visible && groups.iter().all(|(_, visible)| *visible);
+
+            match layer.kind {
+                AsepriteLayerKind::Image => output.push((index, visible)),
+                AsepriteLayerKind::Group => groups.push((layer.child_level, layer.visible)),
+                AsepriteLayerKind::Tilemap => {
+                    warn!("skipping aseprite tilemap layer {:?}", layer.name);
+                }
+            }
+        }
+
+        output
+    }
+}
+
+impl AsepriteLayer {
+    /// GameMaker only has four blend modes, so any other blend mode is treated as normal.
+    pub fn blend_mode(&self) -> usize {
+        match self.blend_mode {
+            0 => 0,
+            1 => 3,
+            16 => 1,
+            17 => 2,
+            other => {
+                warn!(
+                    "aseprite layer {:?} has blend mode {}, which gamemaker doesn't support",
+                    self.name, other
+                );
+                0
+            }
+        }
+    }
+
+    fn to_sprite_layer(&self, visible: bool) -> SpriteLayer {
+        SpriteLayer {
+            visible,
+            is_locked: false,
+            blend_mode: self.blend_mode(),
+            opacity: (self.opacity as f64 / 255.0 * 100.0).round(),
+            display_name: self.name.clone(),
+            common_data: CommonData::new(LayerId::new()),
+        }
+    }
+}
+
+impl AsepriteTag {
+    /// The frames of the tag, in the order it plays them.
+    pub fn frames(&self, frame_count: usize) -> Result<Vec<usize>, AsepriteError> {
+        if self.from > self.to || self.to >= frame_count {
+            return Err(AsepriteError::BadTag(self.name.clone()));
+        }
+
+        let forward = self.from..=self.to;
+        let output = match self.direction {
+            AsepriteDirection::Forward => forward.collect(),
+            AsepriteDirection::Reverse => forward.rev().collect(),
+            AsepriteDirection::PingPong => forward
+                .clone()
+                .chain(
+                    forward
+                        .rev()
+                        .skip(1)
+                        .take((self.to - self.from).saturating_sub(1)),
+                )
+                .collect(),
+            AsepriteDirection::PingPongReverse => forward
+                .clone()
+                .rev()
+                .chain(
+                    forward
+                        .skip(1)
+                        .take((self.to - self.from).saturating_sub(1)),
+                )
+                .collect(),
+        };
+
+        Ok(output)
+    }
+}
+
+fn parse_layer(
+    chunk: &mut Reader<'_>,
+    layer_opacity_valid: bool,
+) -> Result<AsepriteLayer, AsepriteError> {
+    let flags = chunk.u16()?;
+    let kind = match chunk.u16()? {
+        1 => AsepriteLayerKind::Group,
+        2 => AsepriteLayerKind::Tilemap,
+        _ => AsepriteLayerKind::Image,
+    };
+    let child_level = chunk.u16()?;
+    chunk.skip(4)?; // default width and height, which are ignored
+    let blend_mode = chunk.u16()?;
+    let opacity = chunk.u8()?;
+    chunk.skip(3)?;
+    let name = chunk.string()?;
+
+    Ok(AsepriteLayer {
+        name,
+        kind,
+        visible: flags & 1 == 1,
+        background: flags & 8 == 8,
+        child_level,
+        blend_mode,
+        opacity: if layer_opacity_valid { opacity } else { 255 },
+    })
+}
+
+fn parse_cel(
+    chunk: &mut Reader<'_>,
+    frame: usize,
+    color_depth: ColorDepth,
+) -> Result<Option<PendingCel>, AsepriteError> {
+    let layer = chunk.u16()? as usize;
+    let position = (chunk.i16()?, chunk.i16()?);
+    let opacity = chunk.u8()?;
+    let cel_type = chunk.u16()?;
+    chunk.skip(7)?; // z-index and reserved
+
+    let raw = match cel_type {
+        0 => {
+            let width = chunk.u16()? as u32;
+            let height = chunk.u16()? as u32;
+            let len = (width * height) as usize * color_depth.bytes_per_pixel();
+
+            RawCel::Pixels {
+                width,
+                height,
+                pixels: chunk.bytes(len)?.to_vec(),
+            }
+        }
+        1 => RawCel::Linked(chunk.u16()? as usize),
+        2 => {
+            let width = chunk.u16()? as u32;
+            let height = chunk.u16()? as u32;
+
+            // a cel never holds more than its own pixels, so that bounds a zlib bomb
+            let limit = (width * height) as usize * color_depth.bytes_per_pixel();
+            let pixels =
+                miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(chunk.rest(), limit)
+                    .map_err(|e| AsepriteError::BadCompression {
+                        frame,
+                        error: match e {
+                            miniz_oxide::inflate::TINFLStatus::HasMoreOutput => {
+                                format!("more than {} bytes of pixels", limit)
+                            }
+                            e => format!("{:?}", e),
+                        },
+                    })?;
+
+            RawCel::Pixels {
+                width,
+                height,
+                pixels,
+            }
+        }
+        // tilemap cels
+        _ => return Ok(None),
+    };
+
+    Ok(Some(PendingCel {
+        frame,
+        layer,
+        position,
+        opacity,
+        raw,
+    }))
+}
+
+fn parse_tags(chunk: &mut Reader<'_>) -> Result<Vec<AsepriteTag>, AsepriteError> {
+    let count = chunk.u16()?;
+    chunk.skip(8)?;
+
+    (0..count)
+        .map(|_| {
+            let from = chunk.u16()? as usize;
+            let to = chunk.u16()? as usize;
+            let direction = match chunk.u8()? {
+                1 => AsepriteDirection::Reverse,
+                2 => AsepriteDirection::PingPong,
+                3 => AsepriteDirection::PingPongReverse,
+                _ => AsepriteDirection::Forward,
+            };
+            chunk.skip(2 + 6 + 3 + 1)?; // repeat, reserved, and the deprecated tag color
+            let name = chunk.string()?;
+
+            Ok(AsepriteTag {
+                name,
+                from,
+                to,
+                direction,
+            })
+        })
+        .collect()
+}
+
+fn parse_palette(chunk: &mut Reader<'_>, palette: &mut Vec<Rgba<u8>>) -> Result<(), AsepriteError> {
+    let size = chunk.u32()? as usize;
+    let first = chunk.u32()? as usize;
+    let last = chunk.u32()? as usize;
+    chunk.skip(8)?;
+
+    // every entry is at least 6 bytes, so this bounds the palette by the chunk's length
+    let fits = first <= last
+        && last < size
+        && (last - first + 1)
+            .checked_mul(6)
+            .is_some_and(|len| len <= chunk.remaining());
+    if fits == false {
+        return Err(AsepriteError::BadPalette { first, last, size });
+    }
+
+    // colors past `last` would be transparent, as are lookups past the end of the palette
+    palette.resize((last + 1).max(palette.len()), Rgba([0, 0, 0, 0]));
+    for index in first..=last {
+        let flags = chunk.u16()?;
+        let color = Rgba([chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?]);
+        if flags & 1 == 1 {
+            chunk.string()?;
+        }
+
+        if let Some(entry) = palette.get_mut(index) {
+            *entry = color;
+        }
+    }
+
+    Ok(())
+}
+
+/// Old palettes store colors as 8 bit, or 6 bit in the oldest format.
+fn parse_old_palette(
+    chunk: &mut Reader<'_>,
+    six_bit: bool,
+) -> Result<Vec<Rgba<u8>>, AsepriteError> {
+    let scale = |v: u8| {
+        if six_bit {
+            ((v as u32 * 255) / 63) as u8
+        } else {
+            v
+        }
+    };
+
+    let mut palette = vec![Rgba([0, 0, 0, 0]); 256];
+    let mut index = 0;
+    for _ in 0..chunk.u16()? {
+        index += chunk.u8()? as usize;
+        let count = match chunk.u8()? {
+            0 => 256,
+            count => count as usize,
+        };
+
+        for _ in 0..count {
+            let color = Rgba([
+                scale(chunk.u8()?),
+                scale(chunk.u8()?),
+                scale(chunk.u8()?),
+                255,
+            ]);
+            if let Some(entry) = palette.get_mut(index) {
+                *entry = color;
+            }
+            index += 1;
+        }
+    }
+
+    Ok(palette)
+}
+
+fn to_image(
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    color_depth: ColorDepth,
+    palette: &[Rgba<u8>],
+    transparent_index: Option<u8>,
+) -> Result<SpriteImageBuffer, AsepriteError> {
+    let len = (width * height) as usize;
+    let pixels = pixels
+        .get(..len * color_depth.bytes_per_pixel())
+        .ok_or(AsepriteError::UnexpectedEof)?;
+
+    let data = match color_depth {
+        ColorDepth::Rgba => pixels.to_vec(),
+        ColorDepth::Grayscale => pixels
+            .chunks_exact(2)
+            .flat_map(|v| [v[0], v[0], v[0], v[1]])
+            .collect(),
+        ColorDepth::Indexed => pixels
+            .iter()
+            .flat_map(|index| {
+                if Some(*index) == transparent_index {
+                    [0, 0, 0, 0]
+                } else {
+                    palette
+                        .get(*index as usize)
+                        .map(|v| v.0)
+                        .unwrap_or([0, 0, 0, 0])
+                }
+            })
+            .collect(),
+    };
+
+    SpriteImageBuffer::from_raw(width, height, data).ok_or(AsepriteError::UnexpectedEof)
+}
+
+/// Draws a cel onto a layer image at the cel's position, baking in the cel's opacity.
+fn draw_cel(image: &mut SpriteImageBuffer, cel: &AsepriteCel) {
+    for (x, y, pixel) in cel.image.enumerate_pixels() {
+        let dest_x = cel.position.0 as i64 + x as i64;
+        let dest_y = cel.position.1 as i64 + y as i64;
+        if dest_x < 0
+            || dest_y < 0
+            || dest_x >= image.width() as i64
+            || dest_y >= image.height() as i64
+        {
+            continue;
+        }
+
+        let mut pixel = *pixel;
+        pixel[3] = ((pixel[3] as u32 * cel.opacity as u32) / 255) as u8;
+        image.put_pixel(dest_x as u32, dest_y as u32, pixel);
+    }
+}
+
+/// Tag names can have anything in them, but resource names can't.
+fn sanitize_tag_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn gcd(a: u16, b: u16) -> u16 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Reads little endian values out of the file.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], AsepriteError> {
+        let output = self
+            .data
+            .get(self.position..self.position + len)
+            .ok_or(AsepriteError::UnexpectedEof)?;
+        self.position += len;
+
+        Ok(output)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let output = self.data.get(self.position..).unwrap_or_default();
+        self.position = self.data.len();
+
+        output
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), AsepriteError> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, AsepriteError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AsepriteError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, AsepriteError> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, AsepriteError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, AsepriteError> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: u16, data: Vec<u8>) -> Vec<u8> {
+        let mut output = ((data.len() + 6) as u32).to_le_bytes().to_vec();
+        output.extend(chunk_type.to_le_bytes());
+        output.extend(data);
+        output
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut output = (value.len() as u16).to_le_bytes().to_vec();
+        output.extend(value.as_bytes());
+        output
+    }
+
+    fn layer(name: &str, blend_mode: u16, opacity: u8) -> Vec<u8> {
+        let mut data = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        data.extend(blend_mode.to_le_bytes());
+        data.extend([opacity, 0, 0, 0]);
+        data.extend(string(name));
+        chunk(0x2004, data)
+    }
+
+    fn cel(layer: u16, x: i16, y: i16, cel_type: u16, body: Vec<u8>) -> Vec<u8> {
+        let mut data = layer.to_le_bytes().to_vec();
+        data.extend(x.to_le_bytes());
+        data.extend(y.to_le_bytes());
+        data.push(255);
+        data.extend(cel_type.to_le_bytes());
+        data.extend([0; 7]);
+        data.extend(body);
+        chunk(0x2005, data)
+    }
+
+    fn frame(duration: u16, chunks: Vec<Vec<u8>>) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut output = ((body.len() + 16) as u32).to_le_bytes().to_vec();
+        output.extend(0xF1FAu16.to_le_bytes());
+        output.extend((chunks.len() as u16).to_le_bytes());
+        output.extend(duration.to_le_bytes());
+        output.extend([0, 0, 0, 0, 0, 0]);
+        output.extend(body);
+        output
+    }
+
+    /// A 2x2 file with a bottom layer and a top layer, three frames and one tag.
+    fn test_file() -> Vec<u8> {
+        let red_pixel = vec![1, 0, 1, 0, 255, 0, 0, 255];
+        let mut blue = 2u16.to_le_bytes().to_vec();
+        blue.extend(2u16.to_le_bytes());
+        blue.extend(miniz_oxide::deflate::compress_to_vec_zlib(
+            &[0, 0, 255, 255].repeat(4),
+            6,
+        ));
+
+        let mut tag = 1u16.to_le_bytes().to_vec();
+        tag.extend([0; 8]);
+        tag.extend([0, 0, 2, 0, 2]);
+        tag.extend([0; 12]);
+        tag.extend(string("walk cycle"));
+
+        let frames = [
+            frame(
+                100,
+                vec![
+                    layer("bottom", 0, 255),
+                    layer("top", 16, 128),
+                    chunk(0x2018, tag),
+                    cel(0, 1, 1, 0, red_pixel),
+                    cel(1, 0, 0, 2, blue),
+                ],
+            ),
+            frame(200, vec![cel(0, 1, 1, 1, vec![0, 0])]),
+            frame(100, vec![]),
+        ]
+        .concat();
+
+        [header(3), frames].concat()
+    }
+
+    /// The header of a 2x2 rgba file.
+    fn header(frame_count: u16) -> Vec<u8> {
+        let mut header = vec![0; 4];
+        header.extend(0xA5E0u16.to_le_bytes());
+        header.extend(frame_count.to_le_bytes());
+        header.extend(2u16.to_le_bytes());
+        header.extend(2u16.to_le_bytes());
+        header.extend(32u16.to_le_bytes());
+        header.extend(1u32.to_le_bytes());
+        header.resize(128, 0);
+
+        header
+    }
+
+    #[test]
+    fn parsing() {
+        let file = AsepriteFile::parse(&test_file()).unwrap();
+        assert_eq!((file.width, file.height), (2, 2));
+        assert_eq!(file.layers.len(), 2);
+        assert_eq!(file.layers[1].blend_mode(), 1);
+        assert_eq!(file.frames.len(), 3);
+        assert_eq!(file.frames[1].duration, 200);
+
+        // the linked cel is a copy of the first frame's cel
+        assert_eq!(file.frames[1].cels, vec![file.frames[0].cels[0].clone()]);
+        assert_eq!(
+            file.frames[0].cels[1].image.get_pixel(1, 1),
+            &Rgba([0, 0, 255, 255])
+        );
+
+        assert_eq!(file.tags[0].direction, AsepriteDirection::PingPong);
+        assert_eq!(file.tags[0].frames(3).unwrap(), vec![0, 1, 2, 1]);
+        let reverse = AsepriteTag {
+            direction: AsepriteDirection::PingPongReverse,
+            ..file.tags[0].clone()
+        };
+        assert_eq!(reverse.frames(3).unwrap(), vec![2, 1, 0, 1]);
+        assert!(AsepriteFile::parse(&[0; 16]).is_err());
+    }
+
+    #[test]
+    fn sprites() {
+        let file = AsepriteFile::parse(&test_file()).unwrap();
+        let sprites = file
+            .to_sprites(
+                "spr_player",
+                TexturePath::default(),
+                ViewPath::default(),
+                AsepriteTags::Ignore,
+            )
+            .unwrap();
+        assert_eq!(sprites.len(), 1);
+
+        let (sprite, images) = &sprites[0];
+        assert_eq!(sprite.layers[0].display_name, "top");
+        assert_eq!(sprite.layers[0].opacity, 50.0);
+        assert_eq!(sprite.layers[1].display_name, "bottom");
+        assert_eq!(sprite.frames.len(), 3);
+        assert_eq!(sprite.sequence.playback_speed, 10.0);
+        assert_eq!(sprite.sequence.length, 4.0);
+
+        let bottom = sprite.layers[1].common_data.name;
+        let second_frame = images.layer_image(sprite.frames[1].name, bottom).unwrap();
+        assert_eq!(second_frame.get_pixel(1, 1), &Rgba([255, 0, 0, 255]));
+        assert_eq!(second_frame.get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+
+        let sprites = file
+            .to_sprites(
+                "spr_player",
+                TexturePath::default(),
+                ViewPath::default(),
+                AsepriteTags::SeparateSprites,
+            )
+            .unwrap();
+        assert_eq!(sprites.len(), 1);
+        assert_eq!(sprites[0].0.common_data.name, "spr_player_walk_cycle");
+        assert_eq!(sprites[0].0.frames.len(), 4);
+    }
+
+    #[test]
+    fn malformed() {
+        // a palette which claims to be huge, with no colors in it
+        let mut palette = u32::MAX.to_le_bytes().to_vec();
+        palette.extend(0u32.to_le_bytes());
+        palette.extend((u32::MAX - 1).to_le_bytes());
+        palette.extend([0; 8]);
+        let file = [header(1), frame(100, vec![chunk(0x2019, palette)])].concat();
+        assert!(matches!(
+            AsepriteFile::parse(&file),
+            Err(AsepriteError::BadPalette { .. })
+        ));
+
+        // a 1x1 cel which inflates to far more than one pixel
+        let mut bomb = 1u16.to_le_bytes().to_vec();
+        bomb.extend(1u16.to_le_bytes());
+        bomb.extend(miniz_oxide::deflate::compress_to_vec_zlib(&[0; 4096], 6));
+        let file = [
+            header(1),
+            frame(100, vec![layer("bottom", 0, 255), cel(0, 0, 0, 2, bomb)]),
+        ]
+        .concat();
+        assert!(matches!(
+            AsepriteFile::parse(&file),
+            Err(AsepriteError::BadCompression { frame: 0, .. })
+        ));
+    }
+}
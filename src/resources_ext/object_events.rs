@@ -0,0 +1,286 @@
+use crate::{YyResourceHandler, YyResourceHandlerError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+use yy_typings::{EventType, Object};
+
+/// The code GameMaker gives an event which should run its parent's event.
+pub const EVENT_INHERITED_GML: &str = "event_inherited();\n";
+
+/// An event an object responds to, either because it has the event itself, or because one of
+/// its ancestors does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveEvent {
+    pub event_type: EventType,
+    pub source: EventSource,
+}
+
+/// Where an object's event comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EventSource {
+    /// The object has the event, and none of its ancestors do.
+    Own,
+
+    /// The object has the event, replacing the event of the nearest ancestor with it. The
+    /// ancestor's code only runs if the object calls `event_inherited()`.
+    Overrides { ancestor: String },
+
+    /// The object doesn't have the event, so the nearest ancestor with it runs instead.
+    Inherited { ancestor: String },
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ObjectInheritanceError {
+    #[error("couldn't find object {0}")]
+    NoObject(String),
+
+    #[error("object {0} is its own ancestor")]
+    ParentCycle(String),
+}
+
+impl YyResourceHandler<Object> {
+    /// The parent, grandparent and so on of an object, nearest first.
+    pub fn ancestors(&self, object_name: &str) -> Result<Vec<String>, ObjectInheritanceError> {
+        let mut output: Vec<String> = vec![];
+        let mut current = self.object(object_name)?;
+
+        while let Some(parent) = current.parent_object_id.as_ref() {
+            if parent.name == object_name || output.contains(&parent.name) {
+                return Err(ObjectInheritanceError::ParentCycle(parent.name.clone()));
+            }
+
+            current = self.object(&parent.name)?;
+            output.push(parent.name.clone());
+        }
+
+        Ok(output)
+    }
+
+    /// The objects whose parent is the given object, sorted by name.
+    pub fn children(&self, object_name: &str) -> Vec<String> {
+        let mut output: Vec<String> = self
+            .resources()
+            .values()
+            .filter(|v| {
+                v.yy_resource
+                    .parent_object_id
+                    .as_ref()
+                    .is_some_and(|parent| parent.name == object_name)
+            })
+            .map(|v| v.yy_resource.common_data.name.clone())
+            .collect();
+        output.sort();
+
+        output
+    }
+
+    /// Every event an object responds to, sorted by event type, and where each comes from.
+    pub fn effective_events(
+        &self,
+        object_name: &str,
+    ) -> Result<Vec<EffectiveEvent>, ObjectInheritanceError> {
+        let object = self.object(object_name)?;
+
+        // the nearest ancestor with each event
+        let mut inherited: HashMap<EventType, String> = HashMap::new();
+        for ancestor in self.ancestors(object_name)? {
+            for event in self.object(&ancestor)?.event_list.iter() {
+                inherited
+                    .entry(event.event_type)
+                    .or_insert_with(|| ancestor.clone());
+            }
+        }
+
+        let mut output: Vec<EffectiveEvent> = object
+            .event_list
+            .iter()
+            .map(|event| EffectiveEvent {
+                event_type: event.event_type,
+                source: match inherited.remove(&event.event_type) {
+                    Some(ancestor) => EventSource::Overrides { ancestor },
+                    None => EventSource::Own,
+                },
+            })
+            .collect();
+        output.extend(
+            inherited
+                .into_iter()
+                .map(|(event_type, ancestor)| EffectiveEvent {
+                    event_type,
+                    source: EventSource::Inherited { ancestor },
+                }),
+        );
+        output.sort_by_key(|v| v.event_type);
+
+        Ok(output)
+    }
+
+    /// The descendants of an object which have their own version of one of its events,
+    /// sorted by name. Descendants are listed even if an object between them and the given
+    /// object also overrides the event.
+    pub fn event_overrides(
+        &self,
+        object_name: &str,
+        event_type: EventType,
+    ) -> Result<Vec<String>, ObjectInheritanceError> {
+        self.object(object_name)?;
+
+        let mut output = vec![];
+        for data in self.resources().values() {
+            let name = &data.yy_resource.common_data.name;
+            // a broken chain can't lead back to the object, so we don't need its error
+            let ancestors = self.ancestors(name).unwrap_or_default();
+            if ancestors.iter().any(|v| v == object_name)
+                && data
+                    .yy_resource
+                    .event_list
+                    .iter()
+                    .any(|v| v.event_type == event_type)
+            {
+                output.push(name.clone());
+            }
+        }
+        output.sort();
+
+        Ok(output)
+    }
+
+    /// Adds an event to an object whose code calls `event_inherited()`, so it starts off
+    /// running its parent's event. Returns `false` if the object already has the event.
+    ///
+    /// The object's associated data must be loaded.
+    pub fn add_inherited_event(
+        &mut self,
+        object_name: &str,
+        event_type: EventType,
+    ) -> Result<bool, YyResourceHandlerError> {
+        let data = self
+            .get(object_name)
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+        if data.associated_data.is_none() {
+            return Err(YyResourceHandlerError::AssociatedDataNotLoaded);
+        }
+
+        if self.add_event(object_name, event_type) == false {
+            return Ok(false);
+        }
+
+        // safety: `add_event` marked the object for serialization
+        let data = unsafe { self.get_mut(object_name) }.unwrap();
+        data.associated_data
+            .as_mut()
+            .unwrap()
+            .insert(event_type, EVENT_INHERITED_GML.to_string());
+
+        Ok(true)
+    }
+
+    fn object(&self, object_name: &str) -> Result<&Object, ObjectInheritanceError> {
+        self.get(object_name)
+            .map(|v| &v.yy_resource)
+            .ok_or_else(|| ObjectInheritanceError::NoObject(object_name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yy_typings::{CommonData, FilesystemPath, ObjectEvent, Stage};
+
+    fn object(name: &str, parent: Option<&str>, events: &[EventType]) -> Object {
+        Object {
+            common_data: CommonData::new(name.to_string()),
+            parent_object_id: parent.map(|v| FilesystemPath::new("objects", v)),
+            event_list: events
+                .iter()
+                .map(|event_type| ObjectEvent {
+                    event_type: *event_type,
+                    ..ObjectEvent::default()
+                })
+                .collect(),
+            ..Object::default()
+        }
+    }
+
+    #[test]
+    fn inheritance() {
+        let step = EventType::Step(Stage::Main);
+        let mut handler = YyResourceHandler::new();
+        for object in [
+            object("obj_base", None, &[EventType::Create, step]),
+            object("obj_enemy", Some("obj_base"), &[EventType::Create]),
+            object("obj_boss", Some("obj_enemy"), &[step, EventType::Destroy]),
+            object("obj_wall", None, &[]),
+        ] {
+            let events = object
+                .event_list
+                .iter()
+                .map(|v| (v.event_type, String::new()))
+                .collect();
+            handler.load_on_startup(object, Some(events));
+        }
+
+        assert_eq!(
+            handler.ancestors("obj_boss").unwrap(),
+            vec!["obj_enemy".to_string(), "obj_base".to_string()]
+        );
+        assert_eq!(handler.children("obj_base"), vec!["obj_enemy".to_string()]);
+        assert_eq!(
+            handler.effective_events("obj_boss").unwrap(),
+            vec![
+                EffectiveEvent {
+                    event_type: EventType::Create,
+                    source: EventSource::Inherited {
+                        ancestor: "obj_enemy".to_string()
+                    }
+                },
+                EffectiveEvent {
+                    event_type: step,
+                    source: EventSource::Overrides {
+                        ancestor: "obj_base".to_string()
+                    }
+                },
+                EffectiveEvent {
+                    event_type: EventType::Destroy,
+                    source: EventSource::Own
+                },
+            ]
+        );
+        assert_eq!(
+            handler
+                .event_overrides("obj_base", EventType::Create)
+                .unwrap(),
+            vec!["obj_enemy".to_string()]
+        );
+
+        assert!(handler
+            .add_inherited_event("obj_boss", EventType::Create)
+            .unwrap());
+        assert_eq!(
+            handler
+                .get("obj_boss")
+                .unwrap()
+                .associated_data
+                .as_ref()
+                .unwrap()[&EventType::Create],
+            EVENT_INHERITED_GML
+        );
+        assert_eq!(
+            handler
+                .event_overrides("obj_base", EventType::Create)
+                .unwrap(),
+            vec!["obj_boss".to_string(), "obj_enemy".to_string()]
+        );
+
+        handler.load_on_startup(
+            object("obj_wall", Some("obj_wall"), &[]),
+            Some(HashMap::new()),
+        );
+        assert_eq!(
+            handler.ancestors("obj_wall"),
+            Err(ObjectInheritanceError::ParentCycle("obj_wall".to_string()))
+        );
+    }
+}
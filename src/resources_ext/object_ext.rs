@@ -1,11 +1,359 @@
-use yy_typings::{CommonData, EventType, Object, ObjectEvent, TrailingCommaUtility, ViewPath};
+use yy_typings::{
+    CommonData, EventType, Object, ObjectEvent, ObjectProperty, ObjectPropertyTypes,
+    TrailingCommaUtility, ViewPath,
+};
 
+use super::object_physics::{ObjectPhysicsError, PhysicsMaterial, PhysicsShapeUtility};
+use super::object_properties::{check_property_value, default_property_value, ObjectPropertyError};
 use crate::{
-    FileHolder, FileSerializationError, Resource, SerializedData, SerializedDataError, YyResource,
-    YyResourceHandler, YypBoss,
+    directory_manager::DirectoryManager, FileHolder, FileSerializationError, Resource,
+    SerializedData, SerializedDataError, YyResource, YyResourceHandler, YyResourceHandlerError,
+    YypBoss,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
 };
 
-use std::{collections::HashMap, path::Path};
+pub trait ObjectExt: Sized {
+    fn with(self, edit: impl Fn(&mut Self)) -> Self;
+
+    /// Finds a variable defined in this object. Variables defined in its ancestors aren't
+    /// found; see [`YyResourceHandler::property_definition`] for those.
+    fn property(&self, name: &str) -> Option<&ObjectProperty>;
+
+    /// Defines a new variable with the given default value.
+    ///
+    /// Builder version.
+    fn add_property(
+        self,
+        name: &str,
+        var_type: ObjectPropertyTypes,
+        value: &str,
+    ) -> Result<Self, ObjectPropertyError>;
+
+    /// Defines a new variable with the given default value, which must suit its type.
+    fn set_add_property(
+        &mut self,
+        name: &str,
+        var_type: ObjectPropertyTypes,
+        value: &str,
+    ) -> Result<(), ObjectPropertyError>;
+
+    /// Removes a variable, returning it. Overrides of it in children and rooms are left
+    /// as they are.
+    fn set_remove_property(&mut self, name: &str) -> Option<ObjectProperty>;
+
+    /// Changes the type of a variable. If its default value doesn't suit the new type, it's
+    /// reset to the new type's default.
+    fn set_property_type(
+        &mut self,
+        name: &str,
+        var_type: ObjectPropertyTypes,
+    ) -> Result<(), ObjectPropertyError>;
+
+    /// Sets the default value of a variable, which must suit its type.
+    fn set_property_value(&mut self, name: &str, value: &str) -> Result<(), ObjectPropertyError>;
+
+    /// Sets the range of a `Real` or `Integer` variable, or disables it with `None`. The range
+    /// must be finite, and the range of an `Integer` variable must hold a whole number. The
+    /// default value is rounded, if it's an `Integer`, and clamped into the range.
+    fn set_property_range(
+        &mut self,
+        name: &str,
+        range: Option<(f64, f64)>,
+    ) -> Result<(), ObjectPropertyError>;
+
+    /// Sets the items a `List` variable can be set to. If the default value is no longer in
+    /// the list, it's reset to the first item.
+    fn set_property_list(
+        &mut self,
+        name: &str,
+        list_items: Vec<String>,
+        multiselect: bool,
+    ) -> Result<(), ObjectPropertyError>;
+
+    /// Makes the object a physics object, or not. The rest of its physics settings are kept
+    /// either way.
+    fn physics(self, enabled: bool) -> Self;
+
+    /// Sets the shape of the object's fixture, and the points GameMaker stores for it.
+    fn physics_shape(self, shape: PhysicsShapeUtility) -> Result<Self, ObjectPhysicsError>;
+
+    /// Sets the density, restitution, friction and damping of the object's fixture.
+    fn physics_material(self, material: PhysicsMaterial) -> Result<Self, ObjectPhysicsError>;
+
+    /// Sets the collision group. Fixtures in the same positive group always collide, and
+    /// `0` means no group.
+    fn physics_group(self, group: usize) -> Self;
+
+    /// Makes the fixture a sensor, which reports collisions without reacting to them.
+    fn physics_sensor(self, sensor: bool) -> Self;
+    fn physics_kinematic(self, kinematic: bool) -> Self;
+    fn physics_start_awake(self, start_awake: bool) -> Self;
+
+    /// Makes the object a physics object, or not. The rest of its physics settings are kept
+    /// either way.
+    fn set_physics(&mut self, enabled: bool);
+
+    /// Sets the shape of the object's fixture, and the points GameMaker stores for it. If the
+    /// shape is invalid, the object is left unchanged.
+    fn set_physics_shape(&mut self, shape: PhysicsShapeUtility) -> Result<(), ObjectPhysicsError>;
+
+    /// Sets the density, restitution, friction and damping of the object's fixture. If any
+    /// are invalid, the object is left unchanged.
+    fn set_physics_material(&mut self, material: PhysicsMaterial)
+        -> Result<(), ObjectPhysicsError>;
+
+    /// The density, restitution, friction and damping of the object's fixture.
+    fn to_physics_material(&self) -> PhysicsMaterial;
+    fn set_physics_group(&mut self, group: usize);
+    fn set_physics_sensor(&mut self, sensor: bool);
+    fn set_physics_kinematic(&mut self, kinematic: bool);
+    fn set_physics_start_awake(&mut self, start_awake: bool);
+}
+
+impl ObjectExt for Object {
+    fn with(mut self, edit: impl Fn(&mut Self)) -> Self {
+        edit(&mut self);
+        self
+    }
+
+    fn property(&self, name: &str) -> Option<&ObjectProperty> {
+        self.properties.iter().find(|v| v.common_data.name == name)
+    }
+
+    fn add_property(
+        mut self,
+        name: &str,
+        var_type: ObjectPropertyTypes,
+        value: &str,
+    ) -> Result<Self, ObjectPropertyError> {
+        self.set_add_property(name, var_type, value)?;
+        Ok(self)
+    }
+
+    fn set_add_property(
+        &mut self,
+        name: &str,
+        var_type: ObjectPropertyTypes,
+        value: &str,
+    ) -> Result<(), ObjectPropertyError> {
+        if self.property(name).is_some() {
+            return Err(ObjectPropertyError::PropertyExists(name.to_string()));
+        }
+
+        let property = ObjectProperty {
+            common_data: CommonData::new(name.to_string()),
+            var_type,
+            value: value.to_string(),
+            ..ObjectProperty::default()
+        };
+        check_property_value(&property, value)?;
+        self.properties.push(property);
+
+        Ok(())
+    }
+
+    fn set_remove_property(&mut self, name: &str) -> Option<ObjectProperty> {
+        let index = self
+            .properties
+            .iter()
+            .position(|v| v.common_data.name == name)?;
+
+        Some(self.properties.remove(index))
+    }
+
+    fn set_property_type(
+        &mut self,
+        name: &str,
+        var_type: ObjectPropertyTypes,
+    ) -> Result<(), ObjectPropertyError> {
+        let property = property_mut(self, name)?;
+        property.var_type = var_type;
+
+        if check_property_value(property, &property.value).is_err() {
+            property.value = default_property_value(property);
+        }
+
+        Ok(())
+    }
+
+    fn set_property_value(&mut self, name: &str, value: &str) -> Result<(), ObjectPropertyError> {
+        let property = property_mut(self, name)?;
+        check_property_value(property, value)?;
+        property.value = value.to_string();
+
+        Ok(())
+    }
+
+    fn set_property_range(
+        &mut self,
+        name: &str,
+        range: Option<(f64, f64)>,
+    ) -> Result<(), ObjectPropertyError> {
+        let property = property_mut(self, name)?;
+
+        match range {
+            Some((min, max)) => {
+                if matches!(
+                    property.var_type,
+                    ObjectPropertyTypes::Real | ObjectPropertyTypes::Integer
+                ) == false
+                {
+                    return Err(ObjectPropertyError::WrongType(name.to_string()));
+                }
+                // an integer variable needs a whole number in its range
+                let (low, high) = match property.var_type {
+                    ObjectPropertyTypes::Integer => (min.ceil(), max.floor()),
+                    _ => (min, max),
+                };
+                if min.is_finite() == false || max.is_finite() == false || low > high {
+                    return Err(ObjectPropertyError::BadRange(name.to_string()));
+                }
+
+                property.range_enabled = true;
+                property.range_min = min;
+                property.range_max = max;
+
+                if check_property_value(property, &property.value).is_err() {
+                    let value: f64 = property.value.trim().parse().unwrap_or(low);
+                    property.value = match property.var_type {
+                        ObjectPropertyTypes::Integer => {
+                            (value.round().clamp(low, high) as i64).to_string()
+                        }
+                        _ => value.clamp(low, high).to_string(),
+                    };
+                }
+            }
+            None => {
+                property.range_enabled = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_property_list(
+        &mut self,
+        name: &str,
+        list_items: Vec<String>,
+        multiselect: bool,
+    ) -> Result<(), ObjectPropertyError> {
+        let property = property_mut(self, name)?;
+        if property.var_type != ObjectPropertyTypes::List {
+            return Err(ObjectPropertyError::WrongType(name.to_string()));
+        }
+
+        property.list_items = list_items;
+        property.multiselect = multiselect;
+        if check_property_value(property, &property.value).is_err() {
+            property.value = default_property_value(property);
+        }
+
+        Ok(())
+    }
+
+    fn physics(mut self, enabled: bool) -> Self {
+        self.set_physics(enabled);
+        self
+    }
+
+    fn physics_shape(mut self, shape: PhysicsShapeUtility) -> Result<Self, ObjectPhysicsError> {
+        self.set_physics_shape(shape)?;
+        Ok(self)
+    }
+
+    fn physics_material(mut self, material: PhysicsMaterial) -> Result<Self, ObjectPhysicsError> {
+        self.set_physics_material(material)?;
+        Ok(self)
+    }
+
+    fn physics_group(mut self, group: usize) -> Self {
+        self.set_physics_group(group);
+        self
+    }
+
+    fn physics_sensor(mut self, sensor: bool) -> Self {
+        self.set_physics_sensor(sensor);
+        self
+    }
+
+    fn physics_kinematic(mut self, kinematic: bool) -> Self {
+        self.set_physics_kinematic(kinematic);
+        self
+    }
+
+    fn physics_start_awake(mut self, start_awake: bool) -> Self {
+        self.set_physics_start_awake(start_awake);
+        self
+    }
+
+    fn set_physics(&mut self, enabled: bool) {
+        self.physics_object = enabled;
+    }
+
+    fn set_physics_shape(&mut self, shape: PhysicsShapeUtility) -> Result<(), ObjectPhysicsError> {
+        let (shape, points) = shape.to_points()?;
+        self.physics_shape = shape;
+        self.physics_shape_points = points;
+
+        Ok(())
+    }
+
+    fn set_physics_material(
+        &mut self,
+        material: PhysicsMaterial,
+    ) -> Result<(), ObjectPhysicsError> {
+        material.validate()?;
+
+        self.physics_density = material.density;
+        self.physics_restitution = material.restitution;
+        self.physics_friction = material.friction;
+        self.physics_linear_damping = material.linear_damping;
+        self.physics_angular_damping = material.angular_damping;
+
+        Ok(())
+    }
+
+    fn to_physics_material(&self) -> PhysicsMaterial {
+        PhysicsMaterial {
+            density: self.physics_density,
+            restitution: self.physics_restitution,
+            friction: self.physics_friction,
+            linear_damping: self.physics_linear_damping,
+            angular_damping: self.physics_angular_damping,
+        }
+    }
+
+    fn set_physics_group(&mut self, group: usize) {
+        self.physics_group = group;
+    }
+
+    fn set_physics_sensor(&mut self, sensor: bool) {
+        self.physics_sensor = sensor;
+    }
+
+    fn set_physics_kinematic(&mut self, kinematic: bool) {
+        self.physics_kinematic = kinematic;
+    }
+
+    fn set_physics_start_awake(&mut self, start_awake: bool) {
+        self.physics_start_awake = start_awake;
+    }
+}
+
+fn property_mut<'a>(
+    object: &'a mut Object,
+    name: &str,
+) -> Result<&'a mut ObjectProperty, ObjectPropertyError> {
+    object
+        .properties
+        .iter_mut()
+        .find(|v| v.common_data.name == name)
+        .ok_or_else(|| ObjectPropertyError::NoProperty(name.to_string()))
+}
 
 impl YyResource for Object {
     type AssociatedData = HashMap<EventType, String>;
@@ -35,42 +383,29 @@ impl YyResource for Object {
         &mut yyp_boss.objects
     }
 
-    /// This implementation is seriously flawed, but is necesssary to support the GmCode
-    /// downstream crate,
+    /// Writes the code of every event which changed. Files which aren't for one of the
+    /// object's events are left alone; see [`YypBoss::remove_unlisted_object_files`] to
+    /// remove them.
     fn serialize_associated_data(
         &self,
         directory_path: &std::path::Path,
         data: &HashMap<EventType, String>,
     ) -> anyhow::Result<()> {
-        let mut allowed_files = std::collections::HashSet::with_capacity(1 + self.event_list.len());
-        allowed_files.insert(directory_path.join(format!("{}.yy", self.common_data.name)));
-
         for event_type in self.event_list.iter().map(|v| v.event_type) {
             if let Some(gml) = data.get(&event_type) {
-                let path = directory_path.join(format!("{}.gml", event_type.filename()));
-                if path.exists() == false {
-                    log::info!("writing {} to {}", gml, path.display());
+                let path = directory_path.join(event_file_name(event_type));
+
+                // gamemaker watches these files, so we don't touch unchanged ones
+                let unchanged = std::fs::read_to_string(&path).is_ok_and(|v| v == *gml);
+                if unchanged == false {
+                    log::info!("writing {}", path.display());
                     std::fs::write(&path, gml)?;
                 }
-
-                allowed_files.insert(path);
             } else {
                 log::error!("we couldn't find a {} in our associated data, even though it should have been there. not serialized.", event_type);
             }
         }
 
-        let files: std::collections::HashSet<std::path::PathBuf> = directory_path
-            .read_dir()
-            .map(|d| {
-                d.filter_map(|f| f.map(|file_data| file_data.path()).ok())
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        for badfile in files.difference(&allowed_files) {
-            std::fs::remove_file(badfile)?;
-        }
-
         Ok(())
     }
 
@@ -82,7 +417,7 @@ impl YyResource for Object {
         let mut associated_data = HashMap::new();
 
         for event_type in self.event_list.iter().map(|v| v.event_type) {
-            let path = directory_path.join(format!("{}.gml", event_type.filename()));
+            let path = directory_path.join(event_file_name(event_type));
             // chaotically, gamemaker will not make new blank gml scripts
             let val = if path.exists() == false {
                 String::new()
@@ -193,8 +528,7 @@ impl YyResource for Object {
 
     fn cleanup_on_replace(&self, mut files_to_delete: impl FileHolder) {
         for event in self.event_list.iter() {
-            let path = Path::new(&format!("{}.gml", event.event_type.filename())).to_path_buf();
-            files_to_delete.push(path);
+            files_to_delete.push(event_file_name(event.event_type));
         }
     }
 }
@@ -232,6 +566,7 @@ impl YyResourceHandler<Object> {
 
     pub fn remove_event(&mut self, identifier: &str, event_type: EventType) -> bool {
         let output = unsafe { self.get_mut(identifier).unwrap() };
+        let old = output.yy_resource.clone();
 
         if let Some(v) = output
             .yy_resource
@@ -242,14 +577,58 @@ impl YyResourceHandler<Object> {
             output.yy_resource.event_list.remove(v);
             output.associated_data.as_mut().unwrap().remove(&event_type);
 
-            // mark it an serialize...we know this is infallible
-            self.mark_for_serialization(identifier).unwrap();
+            // mark it an serialize, cleaning up the event's file...we know this is infallible
+            self.mark_edited(identifier, &old).unwrap();
 
             true
         } else {
             false
         }
     }
+
+    /// Removes every file in an object's directory which isn't its yy file or the code of
+    /// one of its events, returning the paths which were removed. Folders are left alone.
+    pub(crate) fn remove_unlisted_files(
+        &self,
+        identifier: &str,
+        directory_manager: &DirectoryManager,
+    ) -> Result<Vec<PathBuf>, YyResourceHandlerError> {
+        let object = &self
+            .get(identifier)
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?
+            .yy_resource;
+        let directory = directory_manager.resource_file(&object.relative_yy_directory());
+
+        let mut allowed_files: HashSet<PathBuf> = object
+            .event_list
+            .iter()
+            .map(|v| event_file_name(v.event_type))
+            .collect();
+        allowed_files.insert(PathBuf::from(format!("{}.yy", identifier)));
+
+        let io_error = |e: std::io::Error| FileSerializationError::Io(e.to_string());
+        let mut removed = vec![];
+        for entry in std::fs::read_dir(&directory).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+
+            if path.is_file() && allowed_files.contains(Path::new(file_name)) == false {
+                std::fs::remove_file(&path).map_err(io_error)?;
+                log::info!("removed unlisted file {}", path.display());
+                removed.push(path);
+            }
+        }
+        removed.sort();
+
+        Ok(removed)
+    }
+}
+
+/// The name of the file which holds an event's code.
+fn event_file_name(event_type: EventType) -> PathBuf {
+    PathBuf::from(format!("{}.gml", event_type.filename()))
 }
 
 #[cfg(test)]
@@ -267,4 +646,50 @@ mod tests {
 
         serde_json::to_string(&simple_map).unwrap();
     }
+
+    #[test]
+    fn event_files() {
+        let root = std::env::temp_dir().join(format!("yy-boss-object-{}", std::process::id()));
+        let directory_manager = DirectoryManager::new(&root.join("project.yyp")).unwrap();
+        let directory = root.join("objects/obj_player");
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut object = Object::default();
+        object.common_data.name = "obj_player".to_string();
+        let mut handler = YyResourceHandler::new();
+        handler.load_on_startup(object, Some(HashMap::new()));
+        handler.add_event("obj_player", EventType::Create);
+        handler.add_event("obj_player", EventType::Destroy);
+
+        let write = |handler: &YyResourceHandler<Object>, gml: &str| {
+            let mut data = handler
+                .get("obj_player")
+                .unwrap()
+                .associated_data
+                .clone()
+                .unwrap();
+            data.insert(EventType::Create, gml.to_string());
+            let object = &handler.get("obj_player").unwrap().yy_resource;
+            object.serialize_associated_data(&directory, &data).unwrap();
+        };
+
+        write(&handler, "x = 1;");
+        std::fs::write(directory.join("notes.txt"), "").unwrap();
+        write(&handler, "x = 2;");
+        assert_eq!(
+            std::fs::read_to_string(directory.join("Create_0.gml")).unwrap(),
+            "x = 2;"
+        );
+        assert!(directory.join("notes.txt").exists());
+
+        assert_eq!(
+            handler
+                .remove_unlisted_files("obj_player", &directory_manager)
+                .unwrap(),
+            vec![directory.join("notes.txt")]
+        );
+        assert!(directory.join("Destroy_0.gml").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use yy_typings::{PhysicsShape, PhysicsVec2};
+
+/// The shape of a physics object's fixture, in the object's sprite's pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PhysicsShapeUtility {
+    Circle {
+        x: f32,
+        y: f32,
+        radius: f32,
+    },
+    Box {
+        left: f32,
+        top: f32,
+        right: f32,
+        bottom: f32,
+    },
+
+    /// A convex polygon of between 3 and [`MAX_POLYGON_POINTS`] points, in order around its
+    /// edge.
+    ///
+    /// [`MAX_POLYGON_POINTS`]: PhysicsShapeUtility::MAX_POLYGON_POINTS
+    Polygon(Vec<PhysicsVec2>),
+}
+
+/// How a physics object's fixture moves and collides.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicsMaterial {
+    pub density: f64,
+
+    /// How bouncy the fixture is, between 0 and 1.
+    pub restitution: f64,
+    pub friction: f64,
+    pub linear_damping: f64,
+    pub angular_damping: f64,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ObjectPhysicsError {
+    #[error("a circle's radius must be positive")]
+    BadRadius,
+
+    #[error("a box must have a positive width and height")]
+    EmptyBox,
+
+    #[error("a polygon needs between 3 and 8 points, but was given {0}")]
+    BadPointCount(usize),
+
+    #[error("a polygon's points must make a convex shape")]
+    NotConvex,
+
+    #[error("{0} must be a positive number")]
+    BadMaterial(&'static str),
+
+    #[error("restitution must be between 0 and 1")]
+    BadRestitution,
+}
+
+impl PhysicsShapeUtility {
+    /// The most points GameMaker allows in a polygon.
+    pub const MAX_POLYGON_POINTS: usize = 8;
+
+    /// The shape and shape points GameMaker stores for this shape. Circles are stored as
+    /// their center and a point on their edge, and boxes as their corners, clockwise from
+    /// the top left.
+    pub fn to_points(&self) -> Result<(PhysicsShape, Vec<PhysicsVec2>), ObjectPhysicsError> {
+        let point = |x: f32, y: f32| PhysicsVec2 { x, y };
+
+        match self {
+            PhysicsShapeUtility::Circle { x, y, radius } => {
+                if (*radius > 0.0) == false {
+                    return Err(ObjectPhysicsError::BadRadius);
+                }
+
+                Ok((
+                    PhysicsShape::Circle,
+                    vec![point(*x, *y), point(x + radius, *y)],
+                ))
+            }
+            PhysicsShapeUtility::Box {
+                left,
+                top,
+                right,
+                bottom,
+            } => {
+                if (right > left && bottom > top) == false {
+                    return Err(ObjectPhysicsError::EmptyBox);
+                }
+
+                Ok((
+                    PhysicsShape::Box,
+                    vec![
+                        point(*left, *top),
+                        point(*right, *top),
+                        point(*right, *bottom),
+                        point(*left, *bottom),
+                    ],
+                ))
+            }
+            PhysicsShapeUtility::Polygon(points) => {
+                if (3..=Self::MAX_POLYGON_POINTS).contains(&points.len()) == false {
+                    return Err(ObjectPhysicsError::BadPointCount(points.len()));
+                }
+                if is_convex(points) == false {
+                    return Err(ObjectPhysicsError::NotConvex);
+                }
+
+                Ok((PhysicsShape::ConvexShape, points.clone()))
+            }
+        }
+    }
+}
+
+impl Default for PhysicsMaterial {
+    /// GameMaker's defaults for a new object.
+    fn default() -> Self {
+        Self {
+            density: 0.5,
+            restitution: 0.1,
+            friction: 0.2,
+            linear_damping: 0.1,
+            angular_damping: 0.1,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    pub fn validate(&self) -> Result<(), ObjectPhysicsError> {
+        let fields = [
+            ("density", self.density),
+            ("restitution", self.restitution),
+            ("friction", self.friction),
+            ("linear damping", self.linear_damping),
+            ("angular damping", self.angular_damping),
+        ];
+        for (name, value) in fields {
+            if (value.is_finite() && value >= 0.0) == false {
+                return Err(ObjectPhysicsError::BadMaterial(name));
+            }
+        }
+
+        if self.restitution > 1.0 {
+            return Err(ObjectPhysicsError::BadRestitution);
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether every turn around the polygon goes the same way. Either winding is fine.
+fn is_convex(points: &[PhysicsVec2]) -> bool {
+    let mut sign = 0.0;
+
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        let c = points[(i + 2) % points.len()];
+        let cross = (b.x - a.x) * (c.y - b.y) - (b.y - a.y) * (c.x - b.x);
+
+        if cross == 0.0 || cross * sign < 0.0 {
+            return false;
+        }
+        sign = cross;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectExt;
+    use yy_typings::Object;
+
+    #[test]
+    fn physics() {
+        let point = |x: f32, y: f32| PhysicsVec2 { x, y };
+
+        let object = Object::default()
+            .physics(true)
+            .physics_shape(PhysicsShapeUtility::Circle {
+                x: 8.0,
+                y: 8.0,
+                radius: 4.0,
+            })
+            .unwrap()
+            .physics_material(PhysicsMaterial {
+                restitution: 0.8,
+                ..PhysicsMaterial::default()
+            })
+            .unwrap()
+            .physics_group(2)
+            .physics_sensor(true);
+
+        assert!(object.physics_object && object.physics_sensor);
+        assert_eq!(object.physics_shape, PhysicsShape::Circle);
+        assert_eq!(
+            object.physics_shape_points,
+            vec![point(8.0, 8.0), point(12.0, 8.0)]
+        );
+        assert_eq!(object.physics_restitution, 0.8);
+        assert_eq!(object.physics_density, 0.5);
+        assert_eq!(object.physics_group, 2);
+
+        let square = vec![
+            point(0.0, 0.0),
+            point(4.0, 0.0),
+            point(4.0, 4.0),
+            point(0.0, 4.0),
+        ];
+        assert!(object
+            .clone()
+            .physics_shape(PhysicsShapeUtility::Polygon(square.clone()))
+            .is_ok());
+
+        let mut dented = square;
+        dented.insert(2, point(2.0, 2.0));
+        assert_eq!(
+            object
+                .physics_shape(PhysicsShapeUtility::Polygon(dented))
+                .unwrap_err(),
+            ObjectPhysicsError::NotConvex
+        );
+        assert_eq!(
+            PhysicsMaterial {
+                friction: -1.0,
+                ..PhysicsMaterial::default()
+            }
+            .validate(),
+            Err(ObjectPhysicsError::BadMaterial("friction"))
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use super::ObjectInheritanceError;
+use crate::{YyResource, YyResourceHandler};
+use thiserror::Error;
+use yy_typings::{
+    CommonData, FilesystemPath, Object, ObjectOverrideProperty, ObjectProperty, ObjectPropertyTypes,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ObjectPropertyError {
+    #[error("the object already has a variable named {0}")]
+    PropertyExists(String),
+
+    #[error("couldn't find variable {0}")]
+    NoProperty(String),
+
+    #[error("variable {0} is defined in the object itself, so it can't be overridden there")]
+    NotInherited(String),
+
+    #[error("{value:?} isn't a valid value for a {var_type:?} variable")]
+    BadValue {
+        value: String,
+        var_type: ObjectPropertyTypes,
+    },
+
+    #[error("variable {0} is the wrong type for that")]
+    WrongType(String),
+
+    #[error("the range of variable {0} isn't finite, or holds no valid value")]
+    BadRange(String),
+
+    #[error(transparent)]
+    Inheritance(#[from] ObjectInheritanceError),
+}
+
+/// Checks that a value suits a variable, following how GameMaker writes values: booleans are
+/// `True` or `False`, colours are `$` and hex digits, and multiselect lists are separated by
+/// commas. Strings, expressions and assets can be anything.
+pub(crate) fn check_property_value(
+    property: &ObjectProperty,
+    value: &str,
+) -> Result<(), ObjectPropertyError> {
+    let in_range = |v: f64| {
+        property.range_enabled == false || (property.range_min..=property.range_max).contains(&v)
+    };
+
+    let valid = match property.var_type {
+        ObjectPropertyTypes::Real => value.trim().parse::<f64>().is_ok_and(in_range),
+        ObjectPropertyTypes::Integer => value
+            .trim()
+            .parse::<i64>()
+            .is_ok_and(|v| in_range(v as f64)),
+        ObjectPropertyTypes::Boolean => value == "True" || value == "False",
+        ObjectPropertyTypes::Colour => value.strip_prefix('$').is_some_and(|hex| {
+            (hex.len() == 6 || hex.len() == 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }),
+        ObjectPropertyTypes::List => {
+            let in_list = |v: &str| property.list_items.iter().any(|item| item == v);
+
+            if value.is_empty() {
+                property.multiselect || property.list_items.is_empty()
+            } else if property.multiselect {
+                value.split(',').all(|v| in_list(v.trim()))
+            } else {
+                in_list(value)
+            }
+        }
+        ObjectPropertyTypes::String
+        | ObjectPropertyTypes::Expression
+        | ObjectPropertyTypes::Asset => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ObjectPropertyError::BadValue {
+            value: value.to_string(),
+            var_type: property.var_type,
+        })
+    }
+}
+
+/// The value GameMaker gives a new variable of the property's type.
+pub(crate) fn default_property_value(property: &ObjectProperty) -> String {
+    match property.var_type {
+        ObjectPropertyTypes::Real | ObjectPropertyTypes::Integer => {
+            if property.range_enabled
+                && (property.range_min..=property.range_max).contains(&0.0) == false
+            {
+                match property.var_type {
+                    ObjectPropertyTypes::Integer => (property.range_min.ceil() as i64).to_string(),
+                    _ => property.range_min.to_string(),
+                }
+            } else {
+                "0".to_string()
+            }
+        }
+        ObjectPropertyTypes::Expression => "0".to_string(),
+        ObjectPropertyTypes::Boolean => "False".to_string(),
+        ObjectPropertyTypes::Colour => "$FFFFFFFF".to_string(),
+        ObjectPropertyTypes::List => {
+            if property.multiselect {
+                String::new()
+            } else {
+                property.list_items.first().cloned().unwrap_or_default()
+            }
+        }
+        ObjectPropertyTypes::String | ObjectPropertyTypes::Asset => String::new(),
+    }
+}
+
+impl YyResourceHandler<Object> {
+    /// Finds the definition of a variable an object has, either in the object itself or in
+    /// its nearest ancestor with it, along with the name of the object which defines it.
+    pub fn property_definition(
+        &self,
+        object_name: &str,
+        property_name: &str,
+    ) -> Result<(String, &ObjectProperty), ObjectPropertyError> {
+        let lineage = std::iter::once(object_name.to_string()).chain(self.ancestors(object_name)?);
+
+        for name in lineage {
+            let property = self.get(&name).and_then(|v| {
+                v.yy_resource
+                    .properties
+                    .iter()
+                    .find(|p| p.common_data.name == property_name)
+            });
+
+            if let Some(property) = property {
+                return Ok((name, property));
+            }
+        }
+
+        Err(ObjectPropertyError::NoProperty(property_name.to_string()))
+    }
+
+    /// Overrides the value of a variable an object inherits from one of its ancestors. The
+    /// value must suit the variable's definition. If the variable is already overridden, the
+    /// override's value is replaced.
+    pub fn override_property(
+        &mut self,
+        object_name: &str,
+        property_name: &str,
+        value: &str,
+    ) -> Result<(), ObjectPropertyError> {
+        let (definer, _) = self.property_definition(object_name, property_name)?;
+        if definer == object_name {
+            return Err(ObjectPropertyError::NotInherited(property_name.to_string()));
+        }
+        let override_property = self.make_override(object_name, property_name, value)?;
+
+        // safety: we mark the object for serialization below
+        let object = &mut unsafe { self.get_mut(object_name) }.unwrap().yy_resource;
+        set_override(&mut object.overridden_properties, override_property);
+        self.mark_for_serialization(object_name).unwrap();
+
+        Ok(())
+    }
+
+    /// Removes an object's override of an inherited variable, so it goes back to using its
+    /// ancestor's value. Returns `false` if the variable wasn't overridden.
+    pub fn remove_property_override(
+        &mut self,
+        object_name: &str,
+        property_name: &str,
+    ) -> Result<bool, ObjectPropertyError> {
+        // safety: we only mark the object for serialization if we changed it
+        let object = &mut unsafe { self.get_mut(object_name) }
+            .ok_or_else(|| ObjectInheritanceError::NoObject(object_name.to_string()))?
+            .yy_resource;
+
+        if remove_override(&mut object.overridden_properties, property_name) == false {
+            return Ok(false);
+        }
+        self.mark_for_serialization(object_name).unwrap();
+
+        Ok(true)
+    }
+
+    /// Overrides the value of a variable on a room instance of an object. `overrides` is the
+    /// instance's list of overrides, its `properties` in the room's yy file. Unlike an object,
+    /// an instance can override the variables its object defines, as well as the ones it
+    /// inherits. If the variable is already overridden, the override's value is replaced.
+    ///
+    /// Rooms are read-only in this crate, so this works on the overrides directly, rather
+    /// than on a room.
+    pub fn override_instance_property(
+        &self,
+        object_name: &str,
+        property_name: &str,
+        value: &str,
+        overrides: &mut Vec<ObjectOverrideProperty>,
+    ) -> Result<(), ObjectPropertyError> {
+        let override_property = self.make_override(object_name, property_name, value)?;
+        set_override(overrides, override_property);
+
+        Ok(())
+    }
+
+    /// Makes an override of a variable, checking the value against its definition.
+    fn make_override(
+        &self,
+        object_name: &str,
+        property_name: &str,
+        value: &str,
+    ) -> Result<ObjectOverrideProperty, ObjectPropertyError> {
+        let (definer, property) = self.property_definition(object_name, property_name)?;
+        check_property_value(property, value)?;
+
+        Ok(ObjectOverrideProperty {
+            common_data: CommonData::default(),
+            property_id: Some(FilesystemPath {
+                name: property_name.to_string(),
+                path: FilesystemPath::new_path(Object::SUBPATH_NAME, &definer),
+            }),
+            object_id: FilesystemPath::new(Object::SUBPATH_NAME, &definer),
+            value: value.to_string(),
+            ..ObjectOverrideProperty::default()
+        })
+    }
+}
+
+fn override_name(override_property: &ObjectOverrideProperty) -> Option<&str> {
+    override_property
+        .property_id
+        .as_ref()
+        .map(|v| v.name.as_str())
+}
+
+/// Removes a room instance's override of a variable, so it goes back to using its object's
+/// value. `overrides` is the instance's list of overrides. Returns `false` if the variable
+/// wasn't overridden.
+pub fn remove_instance_property_override(
+    overrides: &mut Vec<ObjectOverrideProperty>,
+    property_name: &str,
+) -> bool {
+    remove_override(overrides, property_name)
+}
+
+fn remove_override(overrides: &mut Vec<ObjectOverrideProperty>, property_name: &str) -> bool {
+    match overrides
+        .iter()
+        .position(|v| override_name(v) == Some(property_name))
+    {
+        Some(index) => {
+            overrides.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+fn set_override(overrides: &mut Vec<ObjectOverrideProperty>, new: ObjectOverrideProperty) {
+    let name = override_name(&new).map(|v| v.to_string());
+
+    match overrides
+        .iter_mut()
+        .find(|v| override_name(v).map(|v| v.to_string()) == name)
+    {
+        Some(existing) => *existing = new,
+        None => overrides.push(new),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectExt;
+    use std::collections::HashMap;
+
+    #[test]
+    fn properties() {
+        let mut base = Object::default()
+            .add_property("hp", ObjectPropertyTypes::Integer, "10")
+            .unwrap()
+            .add_property("alive", ObjectPropertyTypes::Boolean, "True")
+            .unwrap();
+        base.common_data.name = "obj_base".to_string();
+
+        assert!(matches!(
+            base.set_add_property("hp", ObjectPropertyTypes::Real, "0"),
+            Err(ObjectPropertyError::PropertyExists(_))
+        ));
+        assert!(matches!(
+            base.set_property_value("hp", "ten"),
+            Err(ObjectPropertyError::BadValue { .. })
+        ));
+
+        base.set_property_range("hp", Some((20.0, 100.0))).unwrap();
+        assert_eq!(base.property("hp").unwrap().value, "20");
+        base.set_property_range("hp", Some((0.5, 15.5))).unwrap();
+        assert_eq!(base.property("hp").unwrap().value, "15");
+        for range in [(0.2, 0.8), (f64::NEG_INFINITY, 5.0), (0.0, f64::NAN)] {
+            assert_eq!(
+                base.set_property_range("hp", Some(range)),
+                Err(ObjectPropertyError::BadRange("hp".to_string()))
+            );
+        }
+        base.set_property_range("hp", Some((20.0, 100.0))).unwrap();
+        assert_eq!(base.property("hp").unwrap().value, "20");
+
+        base.set_property_type("alive", ObjectPropertyTypes::Colour)
+            .unwrap();
+        assert_eq!(base.property("alive").unwrap().value, "$FFFFFFFF");
+        assert!(base.set_remove_property("alive").is_some());
+
+        let child = Object {
+            common_data: CommonData::new("obj_child".to_string()),
+            parent_object_id: Some(FilesystemPath::new("objects", "obj_base")),
+            ..Object::default()
+        };
+
+        let mut handler = YyResourceHandler::new();
+        handler.load_on_startup(base, Some(HashMap::new()));
+        handler.load_on_startup(child, Some(HashMap::new()));
+
+        assert!(matches!(
+            handler.override_property("obj_child", "hp", "5"),
+            Err(ObjectPropertyError::BadValue { .. })
+        ));
+        assert_eq!(
+            handler.override_property("obj_base", "hp", "50"),
+            Err(ObjectPropertyError::NotInherited("hp".to_string()))
+        );
+        handler.override_property("obj_child", "hp", "30").unwrap();
+        handler.override_property("obj_child", "hp", "40").unwrap();
+
+        let overrides = &handler
+            .get("obj_child")
+            .unwrap()
+            .yy_resource
+            .overridden_properties;
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].value, "40");
+        assert_eq!(overrides[0].object_id.name, "obj_base");
+
+        assert!(handler.remove_property_override("obj_child", "hp").unwrap());
+        assert!(handler.remove_property_override("obj_child", "hp").unwrap() == false);
+
+        // an instance can override its object's own variables, and inherited ones
+        let mut instance_overrides = vec![];
+        handler
+            .override_instance_property("obj_base", "hp", "60", &mut instance_overrides)
+            .unwrap();
+        handler
+            .override_instance_property("obj_child", "hp", "70", &mut instance_overrides)
+            .unwrap();
+        assert!(matches!(
+            handler.override_instance_property("obj_child", "hp", "7", &mut instance_overrides),
+            Err(ObjectPropertyError::BadValue { .. })
+        ));
+        assert!(matches!(
+            handler.override_instance_property("obj_child", "mp", "7", &mut instance_overrides),
+            Err(ObjectPropertyError::NoProperty(_))
+        ));
+        assert_eq!(instance_overrides.len(), 1);
+        assert_eq!(instance_overrides[0].value, "70");
+        assert_eq!(instance_overrides[0].object_id.name, "obj_base");
+
+        assert!(remove_instance_property_override(
+            &mut instance_overrides,
+            "hp"
+        ));
+        assert!(instance_overrides.is_empty());
+        assert!(remove_instance_property_override(&mut instance_overrides, "hp") == false);
+    }
+}
@@ -0,0 +1,466 @@
+use super::{ShaderFile, ShaderKind};
+use crate::{
+    gml::lexer::{tokenize, Token, TokenKind},
+    YyResourceHandler, YyResourceHandlerError,
+};
+use std::collections::BTreeMap;
+use thiserror::Error;
+use yy_typings::{Shader, ShaderType};
+
+const GLSL_PASSTHROUGH_VERTEX: &str = "//
+// Simple passthrough vertex shader
+//
+attribute vec3 in_Position;                  // (x,y,z)
+//attribute vec3 in_Normal;                  // (x,y,z)     unused in this shader.
+attribute vec4 in_Colour;                    // (r,g,b,a)
+attribute vec2 in_TextureCoord;              // (u,v)
+
+varying vec2 v_vTexcoord;
+varying vec4 v_vColour;
+
+void main()
+{
+    vec4 object_space_pos = vec4( in_Position.x, in_Position.y, in_Position.z, 1.0);
+    gl_Position = gm_Matrices[MATRIX_WORLD_VIEW_PROJECTION] * object_space_pos;
+
+    v_vColour = in_Colour;
+    v_vTexcoord = in_TextureCoord;
+}
+";
+
+const GLSL_PASSTHROUGH_PIXEL: &str = "//
+// Simple passthrough fragment shader
+//
+varying vec2 v_vTexcoord;
+varying vec4 v_vColour;
+
+void main()
+{
+    gl_FragColor = v_vColour * texture2D( gm_BaseTexture, v_vTexcoord );
+}
+";
+
+const HLSL_PASSTHROUGH_VERTEX: &str = "//
+// Simple passthrough vertex shader
+//
+struct VertexShaderInput
+{
+    float3 vPosition : POSITION;
+    float4 vColor    : COLOR0;
+    float2 vTexcoord : TEXCOORD0;
+};
+
+struct VertexShaderOutput
+{
+    float4 vPosition : SV_POSITION;
+    float4 vColor    : COLOR0;
+    float2 vTexcoord : TEXCOORD0;
+};
+
+void main(in VertexShaderInput IN, out VertexShaderOutput OUT)
+{
+    OUT.vPosition = mul(gm_Matrices[MATRIX_WORLD_VIEW_PROJECTION], float4(IN.vPosition, 1.0f));
+    OUT.vColor    = IN.vColor;
+    OUT.vTexcoord = IN.vTexcoord;
+}
+";
+
+const HLSL_PASSTHROUGH_PIXEL: &str = "//
+// Simple passthrough fragment shader
+//
+struct PixelShaderInput
+{
+    float4 vPosition : SV_POSITION;
+    float4 vColor    : COLOR0;
+    float2 vTexcoord : TEXCOORD0;
+};
+
+float4 main(in PixelShaderInput IN) : SV_TARGET0
+{
+    return IN.vColor * gm_BaseTextureObject.Sample(gm_BaseTexture, IN.vTexcoord);
+}
+";
+
+/// Precision and interpolation qualifiers, which can come before a GLSL declaration's type.
+const GLSL_QUALIFIERS: [&str; 7] = [
+    "lowp",
+    "mediump",
+    "highp",
+    "flat",
+    "smooth",
+    "noperspective",
+    "centroid",
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ShaderValidationError {
+    #[error("couldn't find shader {0}")]
+    NoShader(String),
+
+    #[error("the code of shader {0} must be loaded to validate it")]
+    NotLoaded(String),
+
+    #[error("the vertex shader is empty or has no main function")]
+    NoVertexMain,
+
+    #[error("the pixel shader is empty or has no main function")]
+    NoPixelMain,
+
+    #[error("the pixel shader declares attribute {0}, but only vertex shaders have attributes")]
+    AttributeInPixelShader(String),
+
+    #[error("the pixel shader reads {0}, which the vertex shader doesn't output")]
+    MissingVarying(String),
+
+    #[error(
+        "{name} is a {vertex_type} in the vertex shader, but a {pixel_type} in the pixel shader"
+    )]
+    VaryingTypeMismatch {
+        name: String,
+        vertex_type: String,
+        pixel_type: String,
+    },
+}
+
+impl ShaderFile {
+    /// GameMaker's default shaders for a language, which draw with the vertex colour and
+    /// the base texture.
+    pub fn passthrough(shader_type: ShaderType) -> Self {
+        let (vertex, pixel) = match shader_type {
+            ShaderType::GlslEs | ShaderType::Glsl => {
+                (GLSL_PASSTHROUGH_VERTEX, GLSL_PASSTHROUGH_PIXEL)
+            }
+            ShaderType::Hlsl => (HLSL_PASSTHROUGH_VERTEX, HLSL_PASSTHROUGH_PIXEL),
+        };
+
+        Self {
+            vertex: vertex.to_string(),
+            pixel: pixel.to_string(),
+        }
+    }
+
+    /// Checks that both stages have a `main` function, and that everything the pixel shader
+    /// reads from the vertex shader is output by it, with the same type. In GLSL and GLSL ES,
+    /// those are `varying`s, and in HLSL 11, they're the semantics of the vertex shader's
+    /// output and the pixel shader's input.
+    ///
+    /// This is only a quick check of the declarations, so it won't catch everything a
+    /// compiler would.
+    pub fn validate(&self, shader_type: ShaderType) -> Result<(), ShaderValidationError> {
+        let vertex = tokenize(&self.vertex);
+        let pixel = tokenize(&self.pixel);
+
+        if has_main(&vertex) == false {
+            return Err(ShaderValidationError::NoVertexMain);
+        }
+        if has_main(&pixel) == false {
+            return Err(ShaderValidationError::NoPixelMain);
+        }
+
+        match shader_type {
+            ShaderType::GlslEs | ShaderType::Glsl => {
+                if let Some(name) = glsl_declarations(&pixel, "attribute").into_keys().next() {
+                    return Err(ShaderValidationError::AttributeInPixelShader(name));
+                }
+
+                check_interface(
+                    &glsl_declarations(&vertex, "varying"),
+                    &glsl_declarations(&pixel, "varying"),
+                )
+            }
+            ShaderType::Hlsl => {
+                // system values, such as `SV_IsFrontFace`, come from the rasterizer instead
+                let mut inputs = hlsl_interface(&pixel, ShaderKind::Frag);
+                inputs.retain(|semantic, _| semantic.starts_with("SV_") == false);
+
+                check_interface(&hlsl_interface(&vertex, ShaderKind::Vertex), &inputs)
+            }
+        }
+    }
+}
+
+impl YyResourceHandler<Shader> {
+    /// Changes the language of a shader, marking it for serialization. Its code isn't
+    /// changed, so it'll usually need replacing as well, such as with
+    /// [`ShaderFile::passthrough`].
+    pub fn set_shader_type(
+        &mut self,
+        name: &str,
+        shader_type: ShaderType,
+    ) -> Result<(), YyResourceHandlerError> {
+        // safety: we mark the shader for serialization below
+        let shader =
+            unsafe { self.get_mut(name) }.ok_or(YyResourceHandlerError::ResourceNotFound)?;
+        if shader.associated_data.is_none() {
+            return Err(YyResourceHandlerError::AssociatedDataNotLoaded);
+        }
+        shader.yy_resource.shader_type = shader_type;
+
+        self.mark_for_serialization(name)
+    }
+
+    /// Validates a shader's code in its language. See [`ShaderFile::validate`].
+    pub fn validate_shader(&self, name: &str) -> Result<(), ShaderValidationError> {
+        let data = self
+            .get(name)
+            .ok_or_else(|| ShaderValidationError::NoShader(name.to_string()))?;
+        let file = data
+            .associated_data
+            .as_ref()
+            .ok_or_else(|| ShaderValidationError::NotLoaded(name.to_string()))?;
+
+        file.validate(data.yy_resource.shader_type)
+    }
+}
+
+fn has_main(tokens: &[Token<'_>]) -> bool {
+    tokens
+        .windows(2)
+        .any(|v| v[0].kind == TokenKind::Identifier && v[0].text == "main" && v[1].text == "(")
+}
+
+fn check_interface(
+    outputs: &BTreeMap<String, String>,
+    inputs: &BTreeMap<String, String>,
+) -> Result<(), ShaderValidationError> {
+    for (name, pixel_type) in inputs {
+        match outputs.get(name) {
+            None => return Err(ShaderValidationError::MissingVarying(name.clone())),
+            Some(vertex_type) if vertex_type != pixel_type => {
+                return Err(ShaderValidationError::VaryingTypeMismatch {
+                    name: name.clone(),
+                    vertex_type: vertex_type.clone(),
+                    pixel_type: pixel_type.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Every GLSL declaration with a storage qualifier, such as `varying`, by name with its type.
+fn glsl_declarations(tokens: &[Token<'_>], storage: &str) -> BTreeMap<String, String> {
+    let mut output = BTreeMap::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+        if token.kind != TokenKind::Identifier || token.text != storage {
+            continue;
+        }
+
+        while tokens
+            .get(i)
+            .is_some_and(|v| GLSL_QUALIFIERS.contains(&v.text))
+        {
+            i += 1;
+        }
+        let Some(var_type) = tokens.get(i) else {
+            break;
+        };
+        i += 1;
+
+        // one or more names, each of which might be an array, up to the `;`
+        let mut expecting_name = true;
+        while let Some(token) = tokens.get(i) {
+            i += 1;
+            match token.text {
+                ";" => break,
+                "," => expecting_name = true,
+                _ if expecting_name && token.kind == TokenKind::Identifier => {
+                    output.insert(token.text.to_string(), var_type.text.to_string());
+                    expecting_name = false;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    output
+}
+
+/// The semantics, with their types, which an HLSL vertex shader's `main` outputs, or which
+/// a pixel shader's `main` takes in. Both can be given as structs or as separate parameters.
+fn hlsl_interface(tokens: &[Token<'_>], kind: ShaderKind) -> BTreeMap<String, String> {
+    let structs = hlsl_structs(tokens);
+    let mut output = BTreeMap::new();
+
+    let Some(main) = tokens
+        .windows(2)
+        .position(|v| v[0].text == "main" && v[1].text == "(")
+    else {
+        return output;
+    };
+
+    // the parameters, split on the commas between them
+    let mut parameters = vec![];
+    let mut depth = 0usize;
+    let mut start = main + 2;
+    let mut i = start;
+    while let Some(token) = tokens.get(i) {
+        match token.text {
+            "(" | "[" => depth += 1,
+            // a stray `]` ends the parameters of a broken `main`
+            ")" | "]" if depth == 0 => break,
+            ")" | "]" => depth -= 1,
+            "," if depth == 0 => {
+                parameters.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parameters.push(&tokens[start..i.min(tokens.len())]);
+
+    let mut add_fields = |declaration: &[Token<'_>]| {
+        if let Some((semantic, var_type)) = hlsl_field(declaration) {
+            output.insert(semantic, var_type);
+        } else if let Some(fields) = declaration
+            .iter()
+            .rev()
+            .nth(1)
+            .and_then(|var_type| structs.get(var_type.text))
+        {
+            output.extend(fields.iter().cloned());
+        }
+    };
+
+    for parameter in parameters {
+        let qualifier = parameter.first().map(|v| v.text).unwrap_or_default();
+        let wanted = match kind {
+            ShaderKind::Vertex => matches!(qualifier, "out" | "inout"),
+            ShaderKind::Frag => matches!(qualifier, "out" | "uniform") == false,
+        };
+
+        if wanted {
+            add_fields(parameter);
+        }
+    }
+
+    // a vertex shader can also return its output
+    if kind == ShaderKind::Vertex && main > 0 {
+        let return_type = tokens[main - 1];
+        let return_semantic = tokens
+            .get(i + 1)
+            .filter(|v| v.text == ":")
+            .and_then(|_| tokens.get(i + 2));
+
+        match return_semantic {
+            Some(semantic) => {
+                output.insert(
+                    normalize_semantic(semantic.text),
+                    return_type.text.to_string(),
+                );
+            }
+            None => {
+                if let Some(fields) = structs.get(return_type.text) {
+                    output.extend(fields.iter().cloned());
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Every struct declared in some HLSL, by name, with its fields' semantics and types.
+fn hlsl_structs<'a>(tokens: &[Token<'a>]) -> BTreeMap<&'a str, Vec<(String, String)>> {
+    let mut output = BTreeMap::new();
+    let mut i = 0;
+
+    while i + 2 < tokens.len() {
+        if tokens[i].text != "struct" || tokens[i + 2].text != "{" {
+            i += 1;
+            continue;
+        }
+
+        let name = tokens[i + 1].text;
+        let body_start = i + 3;
+        let body_end = tokens[body_start..]
+            .iter()
+            .position(|v| v.text == "}")
+            .map_or(tokens.len(), |v| body_start + v);
+
+        let fields = tokens[body_start..body_end]
+            .split(|v| v.text == ";")
+            .filter_map(hlsl_field)
+            .collect();
+        output.insert(name, fields);
+
+        i = body_end;
+    }
+
+    output
+}
+
+/// Reads the semantic and type of a declaration such as `float2 uv : TEXCOORD0`.
+fn hlsl_field(declaration: &[Token<'_>]) -> Option<(String, String)> {
+    let colon = declaration.iter().position(|v| v.text == ":")?;
+    let semantic = declaration.get(colon + 1)?;
+    let var_type = declaration[..colon].iter().rev().nth(1)?;
+
+    Some((normalize_semantic(semantic.text), var_type.text.to_string()))
+}
+
+/// Semantics aren't case sensitive, and a missing index is 0, so `texcoord` is `TEXCOORD0`.
+fn normalize_semantic(semantic: &str) -> String {
+    let mut output = semantic.to_ascii_uppercase();
+    if output.ends_with(|c: char| c.is_ascii_digit()) == false {
+        output.push('0');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        for shader_type in [ShaderType::GlslEs, ShaderType::Glsl, ShaderType::Hlsl] {
+            assert_eq!(
+                ShaderFile::passthrough(shader_type).validate(shader_type),
+                Ok(())
+            );
+        }
+
+        let mut glsl = ShaderFile::passthrough(ShaderType::GlslEs);
+        glsl.pixel = glsl.pixel.replace("varying vec4", "varying highp vec3");
+        assert_eq!(
+            glsl.validate(ShaderType::GlslEs),
+            Err(ShaderValidationError::VaryingTypeMismatch {
+                name: "v_vColour".to_string(),
+                vertex_type: "vec4".to_string(),
+                pixel_type: "vec3".to_string(),
+            })
+        );
+
+        glsl.vertex = String::new();
+        assert_eq!(
+            glsl.validate(ShaderType::GlslEs),
+            Err(ShaderValidationError::NoVertexMain)
+        );
+
+        let mut hlsl = ShaderFile::passthrough(ShaderType::Hlsl);
+        hlsl.pixel = hlsl.pixel.replace(
+            "float4 main(in PixelShaderInput IN)",
+            "float4 main(in PixelShaderInput IN, float3 normal : normal)",
+        );
+        assert_eq!(
+            hlsl.validate(ShaderType::Hlsl),
+            Err(ShaderValidationError::MissingVarying("NORMAL0".to_string()))
+        );
+
+        // a broken `main` ends at the stray bracket, rather than panicking
+        hlsl.pixel = hlsl.pixel.replace(
+            "float4 main(in PixelShaderInput IN, float3 normal : normal)",
+            "float4 main(float2 uv] : TEXCOORD0)",
+        );
+        assert_eq!(hlsl.validate(ShaderType::Hlsl), Ok(()));
+    }
+}
@@ -0,0 +1,491 @@
+use super::{OriginUtility, SpriteExt, SpriteImageBuffer, SpriteImages};
+use image::imageops::FilterType;
+use std::num::NonZeroUsize;
+use thiserror::Error;
+use yy_typings::{BBoxMode, Sprite};
+
+/// A change to the size of a sprite, applied to the image of every frame and layer.
+///
+/// The sprite's origin follows the change: a preset origin, such as `MiddleCenter`, stays at
+/// the same preset on the new canvas, while a custom origin stays on the same pixel of the
+/// sprite's images. A manual bbox and the nine-slice guides also stay on the same pixels,
+/// clamped to the new canvas, and an automatic or full image bbox is recalculated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpriteTransform {
+    /// Crops every image to the rectangle with its top left corner at `x`, `y`.
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+
+    /// Adds transparent pixels to each edge of every image.
+    Pad {
+        left: u32,
+        top: u32,
+        right: u32,
+        bottom: u32,
+    },
+
+    /// Changes the size of the canvas without scaling the images, placing them at `anchor`.
+    /// Growing the canvas pads the images, and shrinking it crops them.
+    Canvas {
+        width: u32,
+        height: u32,
+        anchor: CanvasAnchor,
+    },
+
+    /// Scales every image to the new size.
+    Resize {
+        width: u32,
+        height: u32,
+        filter: FilterType,
+    },
+}
+
+/// Where the images go on a resized canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CanvasAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    MiddleLeft,
+    MiddleCenter,
+    MiddleRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SpriteTransformError {
+    #[error("a sprite's canvas can't be empty")]
+    EmptyCanvas,
+
+    #[error("crop of {size:?} at {position:?} is outside of the {canvas:?} sprite")]
+    CropOutOfBounds {
+        position: (u32, u32),
+        size: (u32, u32),
+        canvas: (u32, u32),
+    },
+
+    #[error("padding of {padding:?} makes the {canvas:?} sprite too large")]
+    PaddingTooLarge {
+        padding: (u32, u32, u32, u32),
+        canvas: (u32, u32),
+    },
+
+    #[error("a {0:?} canvas is too large for a sprite")]
+    CanvasTooLarge((u32, u32)),
+}
+
+impl CanvasAnchor {
+    /// Where the top left corner of the old canvas ends up on the new canvas.
+    pub fn offset(self, old: (u32, u32), new: (u32, u32)) -> (i64, i64) {
+        let free_x = new.0 as i64 - old.0 as i64;
+        let free_y = new.1 as i64 - old.1 as i64;
+
+        let x = match self {
+            CanvasAnchor::TopLeft | CanvasAnchor::MiddleLeft | CanvasAnchor::BottomLeft => 0,
+            CanvasAnchor::TopCenter | CanvasAnchor::MiddleCenter | CanvasAnchor::BottomCenter => {
+                free_x.div_euclid(2)
+            }
+            CanvasAnchor::TopRight | CanvasAnchor::MiddleRight | CanvasAnchor::BottomRight => {
+                free_x
+            }
+        };
+
+        let y = match self {
+            CanvasAnchor::TopLeft | CanvasAnchor::TopCenter | CanvasAnchor::TopRight => 0,
+            CanvasAnchor::MiddleLeft | CanvasAnchor::MiddleCenter | CanvasAnchor::MiddleRight => {
+                free_y.div_euclid(2)
+            }
+            CanvasAnchor::BottomLeft | CanvasAnchor::BottomCenter | CanvasAnchor::BottomRight => {
+                free_y
+            }
+        };
+
+        (x, y)
+    }
+}
+
+impl SpriteTransform {
+    /// The most bytes a single image of a transformed sprite may take up. Anything larger
+    /// would be allocated once for every frame and layer, and is certainly a mistake.
+    pub const MAX_IMAGE_BYTES: usize = 1 << 30;
+
+    /// The size of the canvas after the transform. Padding which overflows saturates.
+    pub fn new_dimensions(&self, old: (u32, u32)) -> (u32, u32) {
+        match *self {
+            SpriteTransform::Crop { width, height, .. }
+            | SpriteTransform::Canvas { width, height, .. }
+            | SpriteTransform::Resize { width, height, .. } => (width, height),
+            SpriteTransform::Pad {
+                left,
+                top,
+                right,
+                bottom,
+            } => (
+                old.0.saturating_add(left).saturating_add(right),
+                old.1.saturating_add(top).saturating_add(bottom),
+            ),
+        }
+    }
+
+    /// Where the top left corner of the old canvas ends up on the new canvas, or `None` if
+    /// the transform scales the images instead.
+    fn offset(&self, old: (u32, u32)) -> Option<(i64, i64)> {
+        match *self {
+            SpriteTransform::Crop { x, y, .. } => Some((-(x as i64), -(y as i64))),
+            SpriteTransform::Pad { left, top, .. } => Some((left as i64, top as i64)),
+            SpriteTransform::Canvas { anchor, .. } => {
+                Some(anchor.offset(old, self.new_dimensions(old)))
+            }
+            SpriteTransform::Resize { .. } => None,
+        }
+    }
+
+    fn validate(&self, old: (u32, u32)) -> Result<(), SpriteTransformError> {
+        let new = self.new_dimensions(old);
+        if new.0 == 0 || new.1 == 0 {
+            return Err(SpriteTransformError::EmptyCanvas);
+        }
+
+        match *self {
+            SpriteTransform::Crop {
+                x,
+                y,
+                width,
+                height,
+            } if x.checked_add(width).is_none_or(|right| right > old.0)
+                || y.checked_add(height).is_none_or(|bottom| bottom > old.1) =>
+            {
+                return Err(SpriteTransformError::CropOutOfBounds {
+                    position: (x, y),
+                    size: (width, height),
+                    canvas: old,
+                });
+            }
+            SpriteTransform::Pad {
+                left,
+                top,
+                right,
+                bottom,
+            } if old
+                .0
+                .checked_add(left)
+                .and_then(|v| v.checked_add(right))
+                .is_none()
+                || old
+                    .1
+                    .checked_add(top)
+                    .and_then(|v| v.checked_add(bottom))
+                    .is_none() =>
+            {
+                return Err(SpriteTransformError::PaddingTooLarge {
+                    padding: (left, top, right, bottom),
+                    canvas: old,
+                });
+            }
+            _ => {}
+        }
+
+        // sprites store positions as i32, and every image is allocated as rgba
+        let too_large = new.0 > i32::MAX as u32
+            || new.1 > i32::MAX as u32
+            || (new.0 as usize)
+                .checked_mul(new.1 as usize)
+                .and_then(|v| v.checked_mul(4))
+                .is_none_or(|bytes| bytes > Self::MAX_IMAGE_BYTES);
+        if too_large {
+            return Err(SpriteTransformError::CanvasTooLarge(new));
+        }
+
+        Ok(())
+    }
+
+    /// Applies the transform to a single image, which must be the size of the old canvas.
+    pub fn apply_to_image(&self, image: &SpriteImageBuffer) -> SpriteImageBuffer {
+        let old = image.dimensions();
+        let new = self.new_dimensions(old);
+
+        match self.offset(old) {
+            Some(offset) => translate(image, new, offset),
+            None => {
+                let filter = match *self {
+                    SpriteTransform::Resize { filter, .. } => filter,
+                    _ => FilterType::Nearest,
+                };
+                image::imageops::resize(image, new.0, new.1, filter)
+            }
+        }
+    }
+
+    /// Applies the transform to a sprite and all of its images.
+    pub fn apply(
+        &self,
+        sprite: &mut Sprite,
+        images: &mut SpriteImages,
+    ) -> Result<(), SpriteTransformError> {
+        let old = (sprite.width.get() as u32, sprite.height.get() as u32);
+        self.validate(old)?;
+        let new = self.new_dimensions(old);
+
+        for image in images.frames.values_mut() {
+            *image = self.apply_to_image(image);
+        }
+        for image in images.layers.values_mut().flat_map(|v| v.values_mut()) {
+            *image = self.apply_to_image(image);
+        }
+
+        // positions on the old canvas to positions on the new one
+        let offset = self.offset(old);
+        let map_x = |x: i64| match offset {
+            Some((dx, _)) => x.saturating_add(dx),
+            None => (x as f64 * new.0 as f64 / old.0 as f64).round() as i64,
+        };
+        let map_y = |y: i64| match offset {
+            Some((_, dy)) => y.saturating_add(dy),
+            None => (y as f64 * new.1 as f64 / old.1 as f64).round() as i64,
+        };
+
+        sprite.width = NonZeroUsize::new(new.0 as usize).unwrap();
+        sprite.height = NonZeroUsize::new(new.1 as usize).unwrap();
+
+        let origin = match OriginUtility::from_origin(
+            sprite.origin,
+            (sprite.sequence.xorigin, sprite.sequence.yorigin),
+        ) {
+            OriginUtility::Custom { x, y } => OriginUtility::Custom {
+                x: map_x(x as i64) as i32,
+                y: map_y(y as i64) as i32,
+            },
+            preset => preset,
+        };
+        let (origin, (xorigin, yorigin)) = origin.to_origin((new.0 as i32, new.1 as i32));
+        sprite.origin = origin;
+        sprite.sequence.xorigin = xorigin;
+        sprite.sequence.yorigin = yorigin;
+
+        // a full image bbox reaches the width and height, so that's as far as a bbox goes
+        let clamp_x = |x: i64| x.clamp(0, new.0 as i64) as i32;
+        let clamp_y = |y: i64| y.clamp(0, new.1 as i64) as i32;
+        match sprite.bbox_mode {
+            BBoxMode::Automatic => sprite.set_automatic_bbox(images),
+            BBoxMode::FullImage => {
+                sprite.bbox_left = 0;
+                sprite.bbox_top = 0;
+                sprite.bbox_right = new.0 as i32;
+                sprite.bbox_bottom = new.1 as i32;
+            }
+            BBoxMode::Manual => {
+                sprite.bbox_left = clamp_x(map_x(sprite.bbox_left as i64));
+                sprite.bbox_top = clamp_y(map_y(sprite.bbox_top as i64));
+                sprite.bbox_right = clamp_x(map_x(sprite.bbox_right as i64));
+                sprite.bbox_bottom = clamp_y(map_y(sprite.bbox_bottom as i64));
+            }
+        }
+
+        // nine-slice guides are measured in from their own edge, so we go through the
+        // positions of the right and bottom guides
+        if let Some(nine_slice) = sprite.nine_slice.as_mut() {
+            let right = old.0 as i64 - nine_slice.right as i64;
+            let bottom = old.1 as i64 - nine_slice.bottom as i64;
+
+            let left = map_x(nine_slice.left as i64).clamp(0, new.0 as i64);
+            let top = map_y(nine_slice.top as i64).clamp(0, new.1 as i64);
+            let right = map_x(right).clamp(left, new.0 as i64);
+            let bottom = map_y(bottom).clamp(top, new.1 as i64);
+
+            nine_slice.left = left as u64;
+            nine_slice.top = top as u64;
+            nine_slice.right = (new.0 as i64 - right) as u64;
+            nine_slice.bottom = (new.1 as i64 - bottom) as u64;
+        }
+
+        Ok(())
+    }
+}
+
+/// Copies an image onto a new, transparent canvas, with its top left corner at `offset`.
+fn translate(
+    image: &SpriteImageBuffer,
+    dimensions: (u32, u32),
+    offset: (i64, i64),
+) -> SpriteImageBuffer {
+    let mut output = SpriteImageBuffer::new(dimensions.0, dimensions.1);
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let new_x = x as i64 + offset.0;
+        let new_y = y as i64 + offset.1;
+
+        if (0..dimensions.0 as i64).contains(&new_x) && (0..dimensions.1 as i64).contains(&new_y) {
+            output.put_pixel(new_x as u32, new_y as u32, *pixel);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+    use std::collections::HashMap;
+    use yy_typings::{FrameId, SpriteSequenceId, TexturePath, ViewPath};
+
+    fn test_sprite() -> (Sprite, SpriteImages, FrameId) {
+        let mut sprite = Sprite::new("spr_box", TexturePath::default(), ViewPath::default())
+            .dimensions(NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(4).unwrap())
+            .origin(OriginUtility::Custom { x: 1, y: 1 }, false);
+        let frame = FrameId::new();
+        sprite.set_frame(frame, SpriteSequenceId::new());
+
+        let mut image = SpriteImageBuffer::new(4, 4);
+        image.put_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        let images = SpriteImages::single_layer(
+            sprite.layers[0].common_data.name,
+            maplit::hashmap! { frame => image },
+        );
+
+        (sprite, images, frame)
+    }
+
+    #[test]
+    fn pad_and_crop() {
+        let (mut sprite, mut images, frame) = test_sprite();
+        let original: HashMap<_, _> = images.frames.clone();
+
+        SpriteTransform::Pad {
+            left: 2,
+            top: 1,
+            right: 0,
+            bottom: 3,
+        }
+        .apply(&mut sprite, &mut images)
+        .unwrap();
+        assert_eq!((sprite.width.get(), sprite.height.get()), (6, 8));
+        assert_eq!((sprite.sequence.xorigin, sprite.sequence.yorigin), (3, 2));
+        assert_eq!(images.frames[&frame].get_pixel(3, 2)[3], 255);
+        assert_eq!((sprite.bbox_left, sprite.bbox_top), (3, 2));
+
+        SpriteTransform::Crop {
+            x: 2,
+            y: 1,
+            width: 4,
+            height: 4,
+        }
+        .apply(&mut sprite, &mut images)
+        .unwrap();
+        assert_eq!(images.frames, original);
+        assert_eq!((sprite.sequence.xorigin, sprite.sequence.yorigin), (1, 1));
+
+        let bad_crop = SpriteTransform::Crop {
+            x: 2,
+            y: 0,
+            width: 4,
+            height: 4,
+        };
+        assert!(matches!(
+            bad_crop.apply(&mut sprite, &mut images),
+            Err(SpriteTransformError::CropOutOfBounds { .. })
+        ));
+
+        // huge transforms are errors, rather than overflowing
+        let far_crop = SpriteTransform::Crop {
+            x: u32::MAX,
+            y: 0,
+            width: 4,
+            height: 4,
+        };
+        assert!(matches!(
+            far_crop.apply(&mut sprite, &mut images),
+            Err(SpriteTransformError::CropOutOfBounds { .. })
+        ));
+        let huge_pad = SpriteTransform::Pad {
+            left: u32::MAX,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        assert!(matches!(
+            huge_pad.apply(&mut sprite, &mut images),
+            Err(SpriteTransformError::PaddingTooLarge { .. })
+        ));
+        let wide_pad = SpriteTransform::Pad {
+            left: i32::MAX as u32,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        assert_eq!(
+            wide_pad.apply(&mut sprite, &mut images),
+            Err(SpriteTransformError::CanvasTooLarge((
+                i32::MAX as u32 + 4,
+                4
+            )))
+        );
+        assert_eq!(images.frames, original);
+    }
+
+    #[test]
+    fn canvas_and_resize() {
+        let (sprite, images, frame) = test_sprite();
+        let mut sprite = sprite.origin(OriginUtility::MiddleCenter, false);
+        let mut images = images;
+
+        SpriteTransform::Canvas {
+            width: 8,
+            height: 2,
+            anchor: CanvasAnchor::MiddleCenter,
+        }
+        .apply(&mut sprite, &mut images)
+        .unwrap();
+        assert_eq!(CanvasAnchor::BottomRight.offset((4, 4), (8, 2)), (4, -2));
+        assert_eq!((sprite.sequence.xorigin, sprite.sequence.yorigin), (4, 1));
+        assert_eq!(images.frames[&frame].get_pixel(3, 0)[3], 255);
+
+        SpriteTransform::Resize {
+            width: 16,
+            height: 4,
+            filter: FilterType::Nearest,
+        }
+        .apply(&mut sprite, &mut images)
+        .unwrap();
+        assert_eq!(images.frames[&frame].dimensions(), (16, 4));
+        assert_eq!((sprite.sequence.xorigin, sprite.sequence.yorigin), (8, 2));
+        assert_eq!((sprite.bbox_left, sprite.bbox_right), (6, 7));
+
+        // a full image bbox covers the canvas, like `bbox_mode` sets it
+        sprite.bbox_mode = BBoxMode::FullImage;
+        SpriteTransform::Crop {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 3,
+        }
+        .apply(&mut sprite, &mut images)
+        .unwrap();
+        assert_eq!((sprite.bbox_right, sprite.bbox_bottom), (10, 3));
+
+        // huge canvases are errors, rather than enormous allocations
+        let huge_canvas = SpriteTransform::Canvas {
+            width: 100_000,
+            height: 100_000,
+            anchor: CanvasAnchor::TopLeft,
+        };
+        let huge_resize = SpriteTransform::Resize {
+            width: 1,
+            height: u32::MAX,
+            filter: FilterType::Nearest,
+        };
+        for transform in [huge_canvas, huge_resize] {
+            assert!(matches!(
+                transform.apply(&mut sprite, &mut images),
+                Err(SpriteTransformError::CanvasTooLarge(..))
+            ));
+        }
+        assert_eq!(images.frames[&frame].dimensions(), (10, 3));
+    }
+}
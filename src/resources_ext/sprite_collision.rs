@@ -0,0 +1,380 @@
+use super::{Bbox, SpriteImageBuffer, SpriteImages};
+use thiserror::Error;
+use yy_typings::{CollisionKind, FrameId, Sprite};
+
+impl Bbox {
+    /// Returns the tightest box around every pixel in the image with an alpha greater than
+    /// `tolerance`, which is how GameMaker computes an "Automatic" bbox. Like GameMaker, the
+    /// bottom right corner is inclusive.
+    ///
+    /// Returns `None` if no pixel is above the tolerance.
+    pub fn from_alpha(image: &SpriteImageBuffer, tolerance: u8) -> Option<Bbox> {
+        let mut output: Option<Bbox> = None;
+
+        for (x, y, pixel) in image.enumerate_pixels() {
+            if pixel[3] <= tolerance {
+                continue;
+            }
+
+            let point = Bbox {
+                top_left: (x as i32, y as i32),
+                bottom_right: (x as i32, y as i32),
+            };
+
+            output = Some(match output {
+                Some(bbox) => bbox.union(point),
+                None => point,
+            });
+        }
+
+        output
+    }
+
+    /// Returns the tightest box around every pixel above `tolerance` in any of the images.
+    /// This is the "Automatic" bbox of a sprite with those images as its frames.
+    ///
+    /// Returns `None` if no pixel in any image is above the tolerance.
+    pub fn from_alpha_frames<'a>(
+        images: impl IntoIterator<Item = &'a SpriteImageBuffer>,
+        tolerance: u8,
+    ) -> Option<Bbox> {
+        images
+            .into_iter()
+            .filter_map(|image| Bbox::from_alpha(image, tolerance))
+            .reduce(Bbox::union)
+    }
+
+    /// Returns the smallest box which contains both boxes.
+    pub fn union(self, other: Bbox) -> Bbox {
+        Bbox {
+            top_left: (
+                self.top_left.0.min(other.top_left.0),
+                self.top_left.1.min(other.top_left.1),
+            ),
+            bottom_right: (
+                self.bottom_right.0.max(other.bottom_right.0),
+                self.bottom_right.1.max(other.bottom_right.1),
+            ),
+        }
+    }
+}
+
+/// Which pixels of an image are solid for collisions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionMask {
+    width: u32,
+    height: u32,
+    solid: Vec<bool>,
+}
+
+impl CollisionMask {
+    /// Creates a mask where every pixel with an alpha greater than `tolerance` is solid,
+    /// which is how GameMaker creates precise masks.
+    pub fn from_alpha(image: &SpriteImageBuffer, tolerance: u8) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            solid: image.pixels().map(|v| v[3] > tolerance).collect(),
+        }
+    }
+
+    /// Creates a mask of the given shape, filling the given bbox, as GameMaker does for
+    /// non-precise collision kinds. Precise kinds, and rotated rectangles, which are
+    /// rectangles until the instance is rotated, are treated as rectangles.
+    pub fn from_shape(kind: CollisionKind, bbox: Bbox, width: u32, height: u32) -> Self {
+        let (left, top) = bbox.top_left;
+        let (right, bottom) = bbox.bottom_right;
+
+        let center_x = (left + right + 1) as f64 / 2.0;
+        let center_y = (top + bottom + 1) as f64 / 2.0;
+        let radius_x = ((right - left + 1) as f64 / 2.0).max(0.5);
+        let radius_y = ((bottom - top + 1) as f64 / 2.0).max(0.5);
+
+        let mut solid = Vec::with_capacity((width * height) as usize);
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let inside = x >= left && x <= right && y >= top && y <= bottom;
+
+                // we measure from the center of the pixel
+                let dx = ((x as f64 + 0.5) - center_x).abs() / radius_x;
+                let dy = ((y as f64 + 0.5) - center_y).abs() / radius_y;
+
+                solid.push(
+                    inside
+                        && match kind {
+                            CollisionKind::Ellipse => dx * dx + dy * dy <= 1.0,
+                            CollisionKind::Diamond => dx + dy <= 1.0,
+                            _ => true,
+                        },
+                );
+            }
+        }
+
+        Self {
+            width,
+            height,
+            solid,
+        }
+    }
+
+    /// Combines masks together, so a pixel is solid if it is solid in any of them. This is
+    /// how GameMaker makes a single precise mask for every frame of a sprite.
+    ///
+    /// Returns `None` if there are no masks or if they aren't all the same size.
+    pub fn union<'a>(masks: impl IntoIterator<Item = &'a CollisionMask>) -> Option<Self> {
+        let mut masks = masks.into_iter();
+        let mut output = masks.next()?.clone();
+
+        for mask in masks {
+            if mask.dimensions() != output.dimensions() {
+                return None;
+            }
+
+            for (solid, other) in output.solid.iter_mut().zip(mask.solid.iter()) {
+                *solid |= *other;
+            }
+        }
+
+        Some(output)
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns if the given pixel is solid. Pixels outside the mask are never solid.
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.solid[(y * self.width + x) as usize]
+    }
+
+    /// The number of solid pixels in the mask.
+    pub fn solid_count(&self) -> usize {
+        self.solid.iter().filter(|v| **v).count()
+    }
+
+    /// The tightest box around every solid pixel, or `None` if no pixel is solid.
+    pub fn bounds(&self) -> Option<Bbox> {
+        self.solid
+            .iter()
+            .enumerate()
+            .filter(|(_, solid)| **solid)
+            .map(|(i, _)| {
+                let point = (
+                    (i as u32 % self.width) as i32,
+                    (i as u32 / self.width) as i32,
+                );
+                Bbox {
+                    top_left: point,
+                    bottom_right: point,
+                }
+            })
+            .reduce(Bbox::union)
+    }
+
+    /// The number of pixels which are solid in one mask but not in the other. Masks of
+    /// different sizes are compared as if the smaller one were padded with empty pixels.
+    pub fn difference(&self, other: &CollisionMask) -> usize {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|(x, y)| self.is_solid(*x, *y) != other.is_solid(*x, *y))
+            .count()
+    }
+}
+
+/// The collision masks of every frame of a sprite, and how well each collision kind
+/// would match them. See [`CollisionReport::new`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionReport {
+    /// The precise mask of each frame, in frame order.
+    pub frames: Vec<FrameMask>,
+
+    /// How well each collision kind matches the precise masks of the frames, from the
+    /// cheapest kind to the most expensive.
+    pub comparisons: Vec<CollisionComparison>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameMask {
+    pub frame: FrameId,
+    pub mask: CollisionMask,
+
+    /// The bounds of the mask, or `None` if the frame has no solid pixels.
+    pub bounds: Option<Bbox>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionComparison {
+    pub collision_kind: CollisionKind,
+
+    /// The number of pixels, summed over every frame, where this kind's mask differs from
+    /// the frame's precise mask.
+    pub mismatched_pixels: usize,
+
+    /// `mismatched_pixels` as a fraction of the solid pixels of every frame. `0.0` is a
+    /// perfect match.
+    pub error: f64,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CollisionMaskError {
+    #[error("frame {0:?} has no image")]
+    MissingFrame(FrameId),
+
+    #[error("frame {frame:?} is {found:?}, but the sprite is {expected:?}")]
+    BadDimensions {
+        frame: FrameId,
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+}
+
+impl CollisionReport {
+    /// The collision kinds we compare, from cheapest to most expensive.
+    pub const KINDS: [CollisionKind; 5] = [
+        CollisionKind::Rectangle,
+        CollisionKind::Diamond,
+        CollisionKind::Ellipse,
+        CollisionKind::Precise,
+        CollisionKind::PrecisePerFrame,
+    ];
+
+    /// Computes the precise mask of every frame of the sprite, using its `collision_tolerance`,
+    /// and compares each collision kind against them. Shapes fill the sprite's bbox.
+    ///
+    /// Every frame must have an image the size of the sprite.
+    pub fn new(sprite: &Sprite, images: &SpriteImages) -> Result<Self, CollisionMaskError> {
+        let expected = (sprite.width.get() as u32, sprite.height.get() as u32);
+
+        let frames = sprite
+            .frames
+            .iter()
+            .map(|frame| {
+                let image = images
+                    .frames
+                    .get(&frame.name)
+                    .ok_or(CollisionMaskError::MissingFrame(frame.name))?;
+
+                if image.dimensions() != expected {
+                    return Err(CollisionMaskError::BadDimensions {
+                        frame: frame.name,
+                        expected,
+                        found: image.dimensions(),
+                    });
+                }
+
+                let mask = CollisionMask::from_alpha(image, sprite.collision_tolerance);
+                Ok(FrameMask {
+                    frame: frame.name,
+                    bounds: mask.bounds(),
+                    mask,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let solid_pixels: usize = frames.iter().map(|v| v.mask.solid_count()).sum();
+        let bbox = Bbox {
+            top_left: (sprite.bbox_left, sprite.bbox_top),
+            bottom_right: (sprite.bbox_right, sprite.bbox_bottom),
+        };
+        let union = CollisionMask::union(frames.iter().map(|v| &v.mask));
+
+        let comparisons = Self::KINDS
+            .iter()
+            .map(|&collision_kind| {
+                let mismatched_pixels = match collision_kind {
+                    CollisionKind::PrecisePerFrame => 0,
+                    CollisionKind::Precise => frames
+                        .iter()
+                        .map(|v| v.mask.difference(union.as_ref().unwrap()))
+                        .sum(),
+                    shape => {
+                        let mask = CollisionMask::from_shape(shape, bbox, expected.0, expected.1);
+                        frames.iter().map(|v| v.mask.difference(&mask)).sum()
+                    }
+                };
+
+                CollisionComparison {
+                    collision_kind,
+                    mismatched_pixels,
+                    error: mismatched_pixels as f64 / solid_pixels.max(1) as f64,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            frames,
+            comparisons,
+        })
+    }
+
+    /// Returns the cheapest collision kind whose error is at most `max_error`. Since
+    /// precise per frame masks are always exact, this always returns something.
+    pub fn cheapest(&self, max_error: f64) -> CollisionKind {
+        self.comparisons
+            .iter()
+            .find(|v| v.error <= max_error)
+            .map(|v| v.collision_kind)
+            .unwrap_or(CollisionKind::PrecisePerFrame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn alpha_bbox() {
+        let mut first = SpriteImageBuffer::new(16, 16);
+        first.put_pixel(3, 4, Rgba([255, 255, 255, 255]));
+        first.put_pixel(10, 5, Rgba([255, 255, 255, 20]));
+
+        let mut second = SpriteImageBuffer::new(16, 16);
+        second.put_pixel(6, 12, Rgba([0, 0, 0, 255]));
+
+        assert_eq!(
+            Bbox::from_alpha(&first, 0),
+            Some(Bbox {
+                top_left: (3, 4),
+                bottom_right: (10, 5)
+            })
+        );
+        assert_eq!(
+            Bbox::from_alpha(&first, 20),
+            Some(Bbox {
+                top_left: (3, 4),
+                bottom_right: (3, 4)
+            })
+        );
+        assert_eq!(
+            Bbox::from_alpha_frames([&first, &second], 20),
+            Some(Bbox {
+                top_left: (3, 4),
+                bottom_right: (6, 12)
+            })
+        );
+        assert_eq!(Bbox::from_alpha(&SpriteImageBuffer::new(4, 4), 0), None);
+    }
+
+    #[test]
+    fn shapes() {
+        let bbox = Bbox {
+            top_left: (0, 0),
+            bottom_right: (7, 7),
+        };
+        let rectangle = CollisionMask::from_shape(CollisionKind::Rectangle, bbox, 10, 10);
+        let ellipse = CollisionMask::from_shape(CollisionKind::Ellipse, bbox, 10, 10);
+        let diamond = CollisionMask::from_shape(CollisionKind::Diamond, bbox, 10, 10);
+
+        assert_eq!(rectangle.solid_count(), 64);
+        assert_eq!(rectangle.bounds(), Some(bbox));
+        assert!(ellipse.solid_count() < rectangle.solid_count());
+        assert!(diamond.solid_count() < ellipse.solid_count());
+        assert!(ellipse.is_solid(0, 0) == false);
+        assert!(ellipse.is_solid(4, 4));
+        assert_eq!(rectangle.difference(&rectangle), 0);
+    }
+}
@@ -0,0 +1,229 @@
+use super::{SpriteExt, SpriteImageBuffer, SpriteImages};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame,
+};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use thiserror::Error;
+use yy_typings::{FrameId, PlaybackSpeed, Sprite};
+
+/// Where each frame of a sprite is in an exported sprite sheet, along with what's needed
+/// to play the frames back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetAtlas {
+    pub sprite: String,
+
+    /// The size of the whole sheet.
+    pub width: u32,
+    pub height: u32,
+
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub origin: (i32, i32),
+    pub playback_speed: f32,
+    pub playback_speed_type: PlaybackSpeed,
+    pub frames: Vec<AtlasFrame>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasFrame {
+    pub frame: FrameId,
+
+    /// The top left corner of the frame in the sheet.
+    pub x: u32,
+    pub y: u32,
+
+    /// How long the frame is shown for, in frames of the sprite's sequence.
+    pub length: f64,
+}
+
+/// The formats a sprite can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpriteExportFormat {
+    /// A PNG of every frame, laid out in a grid, with a JSON [`SheetAtlas`] next to it.
+    Sheet,
+
+    /// A looping animated GIF.
+    Gif,
+}
+
+#[derive(Debug, Error)]
+pub enum SpriteExportError {
+    #[error("couldn't find sprite {0}")]
+    NoSprite(String),
+
+    #[error("the images of the sprite must be loaded to export it")]
+    AssociatedDataNotLoaded,
+
+    #[error("sprite has no frames")]
+    NoFrames,
+
+    #[error("sprite has no image for frame {0}")]
+    MissingFrame(String),
+
+    #[error("couldn't write export -- {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("couldn't encode export -- {0}")]
+    Encoding(String),
+}
+
+/// Lays every frame of a sprite out in a grid, as close to square as possible, in order.
+pub fn sprite_sheet(
+    sprite: &Sprite,
+    images: &SpriteImages,
+) -> Result<(SpriteImageBuffer, SheetAtlas), SpriteExportError> {
+    let frames = frame_images(sprite, images)?;
+
+    let frame_width = sprite.width.get() as u32;
+    let frame_height = sprite.height.get() as u32;
+    let columns = (frames.len() as f64).sqrt().ceil() as u32;
+    let rows = (frames.len() as u32).div_ceil(columns);
+
+    let mut sheet = SpriteImageBuffer::new(columns * frame_width, rows * frame_height);
+    let mut atlas_frames = Vec::with_capacity(frames.len());
+    for (index, (frame, image)) in frames.into_iter().enumerate() {
+        let x = (index as u32 % columns) * frame_width;
+        let y = (index as u32 / columns) * frame_height;
+        image::imageops::replace(&mut sheet, image, x, y);
+
+        atlas_frames.push(AtlasFrame {
+            frame,
+            x,
+            y,
+            length: sprite.frame_length(frame).unwrap_or(1.0),
+        });
+    }
+
+    let atlas = SheetAtlas {
+        sprite: sprite.common_data.name.clone(),
+        width: sheet.width(),
+        height: sheet.height(),
+        frame_width,
+        frame_height,
+        origin: (sprite.sequence.xorigin, sprite.sequence.yorigin),
+        playback_speed: sprite.sequence.playback_speed,
+        playback_speed_type: sprite.sequence.playback_speed_type,
+        frames: atlas_frames,
+    };
+
+    Ok((sheet, atlas))
+}
+
+/// Encodes a sprite as a looping animated GIF, timing each frame by its length and the
+/// sprite's playback speed. Sprites which play back in frames per game frame are timed
+/// against `game_speed`, the number of game frames per second.
+///
+/// GIFs time frames in hundredths of a second, so fast sprites will play slightly off.
+pub fn sprite_gif(
+    sprite: &Sprite,
+    images: &SpriteImages,
+    game_speed: f64,
+    writer: impl Write,
+) -> Result<(), SpriteExportError> {
+    let frames = frame_images(sprite, images)?;
+
+    let frames_per_second = match sprite.sequence.playback_speed_type {
+        PlaybackSpeed::FramesPerSecond => sprite.sequence.playback_speed as f64,
+        PlaybackSpeed::FramesPerGameFrame => sprite.sequence.playback_speed as f64 * game_speed,
+    };
+    let ms_per_frame = if frames_per_second > 0.0 {
+        1000.0 / frames_per_second
+    } else {
+        0.0
+    };
+
+    let mut encoder = GifEncoder::new(writer);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|e| SpriteExportError::Encoding(e.to_string()))?;
+
+    for (frame, image) in frames {
+        let length = sprite.frame_length(frame).unwrap_or(1.0);
+        let delay = Delay::from_numer_denom_ms((length * ms_per_frame).round() as u32, 1);
+
+        encoder
+            .encode_frame(Frame::from_parts(image.clone(), 0, 0, delay))
+            .map_err(|e| SpriteExportError::Encoding(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// The composite image of every frame, in order.
+fn frame_images<'a>(
+    sprite: &Sprite,
+    images: &'a SpriteImages,
+) -> Result<Vec<(FrameId, &'a SpriteImageBuffer)>, SpriteExportError> {
+    if sprite.frames.is_empty() {
+        return Err(SpriteExportError::NoFrames);
+    }
+
+    sprite
+        .frames
+        .iter()
+        .map(|frame| {
+            images
+                .frames
+                .get(&frame.name)
+                .map(|image| (frame.name, image))
+                .ok_or_else(|| SpriteExportError::MissingFrame(frame.name.inner().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+    use std::{collections::HashMap, num::NonZeroUsize};
+    use yy_typings::{SpriteSequenceId, TexturePath, ViewPath};
+
+    fn test_sprite() -> (Sprite, SpriteImages) {
+        let mut sprite = Sprite::new("spr_coin", TexturePath::default(), ViewPath::default())
+            .dimensions(NonZeroUsize::new(2).unwrap(), NonZeroUsize::new(2).unwrap());
+
+        let mut frames = HashMap::new();
+        for i in 0..3 {
+            let frame = FrameId::new();
+            sprite.set_frame(frame, SpriteSequenceId::new());
+            frames.insert(
+                frame,
+                SpriteImageBuffer::from_pixel(2, 2, Rgba([i * 100, 0, 0, 255])),
+            );
+        }
+        let images = SpriteImages::single_layer(sprite.layers[0].common_data.name, frames);
+
+        (sprite, images)
+    }
+
+    #[test]
+    fn sheets() {
+        let (sprite, images) = test_sprite();
+        let (sheet, atlas) = sprite_sheet(&sprite, &images).unwrap();
+
+        assert_eq!(sheet.dimensions(), (4, 4));
+        assert_eq!((atlas.width, atlas.height), (4, 4));
+        assert_eq!(atlas.frames.len(), 3);
+        assert_eq!((atlas.frames[2].x, atlas.frames[2].y), (0, 2));
+        assert_eq!(sheet.get_pixel(2, 0), &Rgba([100, 0, 0, 255]));
+        assert_eq!(sheet.get_pixel(3, 3), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn gifs() {
+        let (sprite, images) = test_sprite();
+        let mut output = vec![];
+        sprite_gif(&sprite, &images, 60.0, &mut output).unwrap();
+        assert!(output.starts_with(b"GIF89a"));
+
+        let empty = sprite.clone().clear_all_frames();
+        assert!(matches!(
+            sprite_gif(&empty, &images, 60.0, vec![]),
+            Err(SpriteExportError::NoFrames)
+        ));
+    }
+}
@@ -1,6 +1,10 @@
+use super::{
+    default_nine_slice, NineSliceError, NineSliceGuides, NineSliceRegion, SpriteImages,
+    SpriteTransform,
+};
 use crate::{
     FileHolder, Resource, SerializedData, SerializedDataError, YyResource, YyResourceHandler,
-    YypBoss,
+    YyResourceHandlerError, YypBoss,
 };
 use anyhow::Context;
 use anyhow::Result as AnyResult;
@@ -22,6 +26,7 @@ use yy_typings::SpriteSequence;
 use yy_typings::SpriteSequenceId;
 use yy_typings::SpriteZeroChannel;
 use yy_typings::TexturePath;
+use yy_typings::TileMode;
 use yy_typings::Track;
 use yy_typings::TrailingCommaUtility;
 use yy_typings::ViewPath;
@@ -39,6 +44,11 @@ pub trait SpriteExt: Sized {
     ) -> Sprite;
     fn parent(self, parent: ViewPath) -> Sprite;
     fn bbox_mode(self, f: impl Fn(i32, i32) -> BboxModeUtility) -> Self;
+
+    /// Sets the bbox mode to automatic, and computes the bbox from the alpha of every frame,
+    /// using the sprite's `collision_tolerance`. If every frame is fully transparent, the
+    /// bbox covers the full image.
+    fn set_automatic_bbox(&mut self, images: &SpriteImages);
     fn collision_kind(self, collision_kind: CollisionKind) -> Self;
     /// Clears all of the frames from the given image. Generally speaking,
     /// a sprite should have at least one frame when imported into GMS2, but this
@@ -50,11 +60,76 @@ pub trait SpriteExt: Sized {
     fn playback_speed(self, pback_speed: PlaybackSpeed, speed: f32) -> Self;
     fn dimensions(self, width: NonZeroUsize, height: NonZeroUsize) -> Self;
 
+    /// Enables or disables nine-slicing. If the sprite has no nine-slice yet, a default
+    /// one with no guides is made.
+    fn nine_slice(self, enabled: bool) -> Self;
+
+    /// Sets the nine-slice guides, which must fit within the sprite's dimensions, so set the
+    /// dimensions first. This does not enable nine-slicing.
+    fn nine_slice_guides(self, guides: NineSliceGuides) -> Result<Self, NineSliceError>;
+
+    /// Sets the tile mode of a single nine-slice region.
+    fn nine_slice_tile_mode(self, region: NineSliceRegion, tile_mode: TileMode) -> Self;
+
     /// Clears all of the frames from the given image. Generally speaking,
     /// a sprite should have at least one frame when imported into GMS2, but this
     /// function will leave it entirely bare.
     fn set_clear_all_frames(&mut self);
     fn set_frame(&mut self, frame_id: FrameId, sprite_sequence_id: SpriteSequenceId);
+
+    /// Inserts a frame at the given index. If the index is past the end, the frame is
+    /// added at the end.
+    fn set_insert_frame(
+        &mut self,
+        index: usize,
+        frame_id: FrameId,
+        sprite_sequence_id: SpriteSequenceId,
+    );
+
+    /// Removes a frame and its keyframe. Returns `false` if the frame doesn't exist.
+    fn set_remove_frame(&mut self, frame_id: FrameId) -> bool;
+
+    /// Moves a frame to the given index. If the index is past the end, the frame is moved
+    /// to the end. Returns `false` if the frame doesn't exist.
+    fn set_move_frame(&mut self, frame_id: FrameId, index: usize) -> bool;
+
+    /// Returns how long a frame is shown for, in frames of the sprite's sequence.
+    fn frame_length(&self, frame_id: FrameId) -> Option<f64>;
+
+    /// Sets how long a frame is shown for, in frames of the sprite's sequence, and moves the
+    /// frames after it to match. Returns `false` if the frame doesn't exist or the length
+    /// isn't positive.
+    ///
+    /// Broadcast messages are out of scope. They live in the sequence's `events`, whose
+    /// keyframes `yy-typings` types as `Vec<()>`, so there's nothing to write a message into
+    /// until the typings grow a keyframe type for them.
+    fn set_frame_length(&mut self, frame_id: FrameId, length: f64) -> bool;
+
+    /// Adds a layer at the given index, where `0` is the top layer. If the index is past
+    /// the end, the layer is added at the bottom.
+    fn set_add_layer(&mut self, index: usize, layer: SpriteLayer);
+
+    /// Removes a layer, returning it. Sprites must always have at least one layer, so the
+    /// last layer cannot be removed.
+    fn set_remove_layer(&mut self, layer: LayerId) -> Option<SpriteLayer>;
+
+    /// Moves a layer to the given index, where `0` is the top layer. If the index is past
+    /// the end, the layer is moved to the bottom. Returns `false` if the layer doesn't exist.
+    fn set_move_layer(&mut self, layer: LayerId, index: usize) -> bool;
+
+    /// Sets the display name of a layer. Returns `false` if the layer doesn't exist.
+    fn set_rename_layer(&mut self, layer: LayerId, display_name: &str) -> bool;
+
+    /// Enables or disables nine-slicing. If the sprite has no nine-slice yet, a default
+    /// one with no guides is made.
+    fn set_nine_slice_enabled(&mut self, enabled: bool);
+
+    /// Sets the nine-slice guides, which must fit within the sprite's dimensions. If they
+    /// don't, the sprite is left unchanged. This does not enable nine-slicing.
+    fn set_nine_slice_guides(&mut self, guides: NineSliceGuides) -> Result<(), NineSliceError>;
+
+    /// Sets the tile mode of a single nine-slice region.
+    fn set_nine_slice_tile_mode(&mut self, region: NineSliceRegion, tile_mode: TileMode);
 }
 
 impl SpriteExt for Sprite {
@@ -135,17 +210,44 @@ impl SpriteExt for Sprite {
         self
     }
 
+    fn set_automatic_bbox(&mut self, images: &SpriteImages) {
+        let frames = self
+            .frames
+            .iter()
+            .filter_map(|frame| images.frames.get(&frame.name));
+
+        // with nothing to measure, we cover the canvas, like a full image bbox
+        let canvas = (self.width.get() as i32, self.height.get() as i32);
+        let bbox = Bbox::from_alpha_frames(frames, self.collision_tolerance)
+            .unwrap_or_else(|| BboxModeUtility::FullImage.to_bbox(canvas).1);
+
+        self.bbox_mode = BBoxMode::Automatic;
+        self.bbox_left = bbox.top_left.0;
+        self.bbox_top = bbox.top_left.1;
+        self.bbox_right = bbox.bottom_right.0;
+        self.bbox_bottom = bbox.bottom_right.1;
+    }
+
     fn set_frame(&mut self, frame_name: FrameId, sprite_sequence_id: SpriteSequenceId) {
+        self.set_insert_frame(self.frames.len(), frame_name, sprite_sequence_id);
+    }
+
+    fn set_insert_frame(
+        &mut self,
+        index: usize,
+        frame_name: FrameId,
+        sprite_sequence_id: SpriteSequenceId,
+    ) {
         let path_to_sprite = format!("sprites/{0}/{0}.yy", self.common_data.name);
         let path_to_sprite = Path::new(&path_to_sprite);
         // Update the Frame
-        self.frames.push(CommonData::new(frame_name));
+        let index = index.min(self.frames.len());
+        self.frames.insert(index, CommonData::new(frame_name));
 
         // Update the Sequence
         let track: &mut Track = &mut self.sequence.tracks[0];
         track.keyframes.keyframes.push(SpriteKeyframe {
             id: sprite_sequence_id,
-            key: self.frames.len() as f64 - 1.0,
             channels: Channels {
                 zero: SpriteZeroChannel {
                     id: FilesystemPath {
@@ -157,7 +259,67 @@ impl SpriteExt for Sprite {
             },
             ..SpriteKeyframe::default()
         });
-        self.sequence.length = self.frames.len() as f64;
+        sync_frame_track(self);
+    }
+
+    fn set_remove_frame(&mut self, frame_id: FrameId) -> bool {
+        match self.frames.iter().position(|v| v.name == frame_id) {
+            Some(index) => {
+                self.frames.remove(index);
+                sync_frame_track(self);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn set_move_frame(&mut self, frame_id: FrameId, index: usize) -> bool {
+        match self.frames.iter().position(|v| v.name == frame_id) {
+            Some(old_index) => {
+                let frame = self.frames.remove(old_index);
+                let index = index.min(self.frames.len());
+                self.frames.insert(index, frame);
+                sync_frame_track(self);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn frame_length(&self, frame_id: FrameId) -> Option<f64> {
+        let name = frame_id.inner().to_string();
+
+        self.sequence.tracks[0]
+            .keyframes
+            .keyframes
+            .iter()
+            .find(|kf| kf.channels.zero.id.name == name)
+            .map(|kf| kf.length)
+    }
+
+    fn set_frame_length(&mut self, frame_id: FrameId, length: f64) -> bool {
+        if length.is_finite() == false || length <= 0.0 {
+            return false;
+        }
+
+        let name = frame_id.inner().to_string();
+        let keyframe = self.sequence.tracks[0]
+            .keyframes
+            .keyframes
+            .iter_mut()
+            .find(|kf| kf.channels.zero.id.name == name);
+
+        match keyframe {
+            Some(keyframe) => {
+                keyframe.length = length;
+                sync_frame_track(self);
+
+                true
+            }
+            None => false,
+        }
     }
 
     /// Test
@@ -174,6 +336,47 @@ impl SpriteExt for Sprite {
         track.keyframes.keyframes.clear();
     }
 
+    fn set_add_layer(&mut self, index: usize, layer: SpriteLayer) {
+        let index = index.min(self.layers.len());
+        self.layers.insert(index, layer);
+    }
+
+    fn set_remove_layer(&mut self, layer: LayerId) -> Option<SpriteLayer> {
+        if self.layers.len() == 1 {
+            return None;
+        }
+
+        let index = self
+            .layers
+            .iter()
+            .position(|v| v.common_data.name == layer)?;
+
+        Some(self.layers.remove(index))
+    }
+
+    fn set_move_layer(&mut self, layer: LayerId, index: usize) -> bool {
+        match self.layers.iter().position(|v| v.common_data.name == layer) {
+            Some(old_index) => {
+                let layer = self.layers.remove(old_index);
+                let index = index.min(self.layers.len());
+                self.layers.insert(index, layer);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn set_rename_layer(&mut self, layer: LayerId, display_name: &str) -> bool {
+        match self.layers.iter_mut().find(|v| v.common_data.name == layer) {
+            Some(layer) => {
+                layer.display_name = display_name.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
     fn collision_kind(self, collision_kind: CollisionKind) -> Self {
         self.with(|me| {
             me.collision_kind = collision_kind;
@@ -204,10 +407,71 @@ impl SpriteExt for Sprite {
             me.height = height;
         })
     }
+
+    fn nine_slice(self, enabled: bool) -> Self {
+        self.with(|me| me.set_nine_slice_enabled(enabled))
+    }
+
+    fn nine_slice_guides(mut self, guides: NineSliceGuides) -> Result<Self, NineSliceError> {
+        self.set_nine_slice_guides(guides)?;
+        Ok(self)
+    }
+
+    fn nine_slice_tile_mode(self, region: NineSliceRegion, tile_mode: TileMode) -> Self {
+        self.with(|me| me.set_nine_slice_tile_mode(region, tile_mode.clone()))
+    }
+
+    fn set_nine_slice_enabled(&mut self, enabled: bool) {
+        self.nine_slice
+            .get_or_insert_with(default_nine_slice)
+            .enabled = enabled;
+    }
+
+    fn set_nine_slice_guides(&mut self, guides: NineSliceGuides) -> Result<(), NineSliceError> {
+        guides.validate(self)?;
+
+        let nine_slice = self.nine_slice.get_or_insert_with(default_nine_slice);
+        nine_slice.left = guides.left;
+        nine_slice.top = guides.top;
+        nine_slice.right = guides.right;
+        nine_slice.bottom = guides.bottom;
+
+        Ok(())
+    }
+
+    fn set_nine_slice_tile_mode(&mut self, region: NineSliceRegion, tile_mode: TileMode) {
+        self.nine_slice
+            .get_or_insert_with(default_nine_slice)
+            .tile_mode[region.index()] = tile_mode;
+    }
+}
+
+/// Puts the keyframes of the frame track in the same order as `frames`, dropping any keyframe
+/// which no longer has a frame, and then recalculates each keyframe's key and the length
+/// of the sequence.
+fn sync_frame_track(sprite: &mut Sprite) {
+    let track: &mut Track = &mut sprite.sequence.tracks[0];
+    let mut keyframes: HashMap<String, SpriteKeyframe> = track
+        .keyframes
+        .keyframes
+        .drain(..)
+        .map(|kf| (kf.channels.zero.id.name.clone(), kf))
+        .collect();
+
+    let mut key = 0.0;
+    for frame in sprite.frames.iter() {
+        if let Some(mut keyframe) = keyframes.remove(&frame.name.inner().to_string()) {
+            keyframe.key = key;
+            key += keyframe.length;
+            track.keyframes.keyframes.push(keyframe);
+        }
+    }
+
+    sprite.sequence.length = key;
 }
 
 impl YyResource for Sprite {
-    type AssociatedData = HashMap<FrameId, SpriteImageBuffer>;
+    type AssociatedData = SpriteImages;
     const SUBPATH_NAME: &'static str = "sprites";
     const RESOURCE: Resource = Resource::Sprite;
 
@@ -251,13 +515,9 @@ impl YyResource for Sprite {
         data: &Self::AssociatedData,
     ) -> AnyResult<()> {
         let layers_path = directory_path.join("layers");
-        if layers_path.exists() == false {
-            std::fs::create_dir(&layers_path)?;
-        }
 
-        for (frame_id, image) in data {
+        for (frame_id, image) in data.frames.iter() {
             let inner_id_string = frame_id.inner().to_string();
-            let image: &ImageBuffer<_, _> = image;
 
             // Make the Core Image:
             let path = directory_path.join(&inner_id_string).with_extension("png");
@@ -265,51 +525,70 @@ impl YyResource for Sprite {
                 format!("We couldn't serialize the Core Image at path {:?}", path)
             })?;
 
-            // Make the folder and layer image:
+            // Make the folder and layer images:
             let folder_path = layers_path.join(&inner_id_string);
-            if folder_path.exists() == false {
-                std::fs::create_dir(&folder_path)?;
-            }
+            std::fs::create_dir_all(&folder_path)?;
 
-            let image_layer_id = self
-                .layers
-                .first()
-                .ok_or_else(|| anyhow::anyhow!("All Sprites *must* have a single SpriteLayer!"))?
-                .common_data
-                .name
-                .inner()
-                .to_string();
-
-            let final_layer_path = folder_path.join(&image_layer_id).with_extension("png");
-            image
-                .save(&final_layer_path)
-                .with_context(|| format!("We couldn't save an Image to {:?}", final_layer_path))?;
+            let layer_images = data.layers.get(frame_id);
+            let blank = SpriteImageBuffer::new(self.width.get() as u32, self.height.get() as u32);
+
+            for (i, layer) in self.layers.iter().enumerate() {
+                let layer_image = match layer_images {
+                    Some(layer_images) => layer_images.get(&layer.common_data.name),
+                    // a frame without layers is a single layer frame...
+                    None if i == 0 => Some(image),
+                    None => None,
+                };
+
+                // GameMaker wants every layer to have an image, even if it's blank
+                let layer_image = layer_image.unwrap_or(&blank);
+
+                let final_layer_path = folder_path
+                    .join(layer.common_data.name.inner().to_string())
+                    .with_extension("png");
+                layer_image.save(&final_layer_path).with_context(|| {
+                    format!("We couldn't save an Image to {:?}", final_layer_path)
+                })?;
+            }
         }
 
         Ok(())
     }
 
-    // WE DON'T HANDLE LAYERS AT ALL IN THIS CODE --
-    // WE WILL EVENTUALLY.
     fn deserialize_associated_data(
         &self,
         dir_path: &Path,
         _: &TrailingCommaUtility,
-    ) -> Result<HashMap<FrameId, SpriteImageBuffer>, SerializedDataError> {
-        let mut output = HashMap::new();
+    ) -> Result<SpriteImages, SerializedDataError> {
+        fn open(path: &Path) -> Result<SpriteImageBuffer, SerializedDataError> {
+            image::open(path).map(|v| v.to_rgba8()).map_err(|e| {
+                SerializedDataError::BadData(format!("we couldn't read {:#?} -- {}", path, e))
+            })
+        }
+
+        let mut output = SpriteImages::default();
 
         for frame in self.frames.iter() {
-            let path_to_image = dir_path.join(format!("{}.png", frame.name.inner()));
-
-            match image::open(&path_to_image) {
-                Ok(image) => output.insert(frame.name, image.to_rgba8()),
-                Err(e) => {
-                    return Err(SerializedDataError::BadData(format!(
-                        "we couldn't read {:#?} -- {}",
-                        path_to_image, e
-                    )));
+            let inner_id_string = frame.name.inner().to_string();
+            let path_to_image = dir_path.join(&inner_id_string).with_extension("png");
+            output.frames.insert(frame.name, open(&path_to_image)?);
+
+            // layer images are optional -- without any, this is a single layer frame.
+            let folder_path = dir_path.join("layers").join(&inner_id_string);
+            let mut layer_images = HashMap::new();
+            for layer in self.layers.iter() {
+                let path = folder_path
+                    .join(layer.common_data.name.inner().to_string())
+                    .with_extension("png");
+
+                if path.exists() {
+                    layer_images.insert(layer.common_data.name, open(&path)?);
                 }
-            };
+            }
+
+            if layer_images.is_empty() == false {
+                output.layers.insert(frame.name, layer_images);
+            }
         }
 
         Ok(output)
@@ -319,13 +598,28 @@ impl YyResource for Sprite {
         working_directory: &Path,
         associated_data: &Self::AssociatedData,
     ) -> Result<SerializedData, SerializedDataError> {
-        for (frame_id, img) in associated_data {
+        for (frame_id, img) in associated_data.frames.iter() {
             let path = working_directory.join(format!("{}.png", frame_id.inner()));
 
             img.save(&path)
                 .map_err(SerializedDataError::CouldNotWriteImage)?;
         }
 
+        for (frame_id, layer_images) in associated_data.layers.iter() {
+            let folder_path = working_directory
+                .join("layers")
+                .join(frame_id.inner().to_string());
+            std::fs::create_dir_all(&folder_path)
+                .map_err(|e| SerializedDataError::InnerError(e.to_string()))?;
+
+            for (layer_id, img) in layer_images {
+                let path = folder_path.join(format!("{}.png", layer_id.inner()));
+
+                img.save(&path)
+                    .map_err(SerializedDataError::CouldNotWriteImage)?;
+            }
+        }
+
         Ok(SerializedData::Filepath {
             data: working_directory.to_owned(),
         })
@@ -340,7 +634,7 @@ impl YyResource for Sprite {
             SerializedData::Value { .. } => Err(SerializedDataError::CannotUseValue),
             SerializedData::Filepath { data: p } => self.deserialize_associated_data(p, tcu),
             SerializedData::DefaultValue => {
-                let output = self
+                let frames = self
                     .frames
                     .iter()
                     .map(|name| {
@@ -354,29 +648,344 @@ impl YyResource for Sprite {
                     })
                     .collect();
 
-                Ok(output)
+                Ok(SpriteImages {
+                    frames,
+                    ..SpriteImages::default()
+                })
             }
         }
     }
 
     fn cleanup_on_replace(&self, mut files: impl FileHolder) {
-        // first, clean up the layer folders...
-        let base_path = Path::new(&self.common_data.name);
-        let layers_path = base_path.join("layers");
+        // these paths are relative to the sprite's directory
+        let layers_path = Path::new("layers");
 
-        // clean up the composite image...
         for frame in self.frames.iter() {
             let name = frame.name.inner().to_string();
-            let path = Path::new(&name);
-            files.push(layers_path.join(path));
 
-            let mut file = path.to_owned();
-            file.set_extension("png");
-            files.push(base_path.join(file));
+            // clean up the layer folder, and its layer images, so an edit which removes
+            // a layer cleans up just that layer's images...
+            let frame_layers_path = layers_path.join(&name);
+            for layer in self.layers.iter() {
+                let layer_file = format!("{}.png", layer.common_data.name.inner());
+                files.push(frame_layers_path.join(layer_file));
+            }
+            files.push(frame_layers_path);
+
+            // clean up the composite image...
+            files.push(Path::new(&name).with_extension("png"));
         }
     }
 }
 
+impl YyResourceHandler<Sprite> {
+    /// Adds a layer to a sprite at the given index, where `0` is the top layer, and
+    /// recomposites the sprite. `images` are the layer's images for each frame -- any
+    /// frame without an image is left transparent.
+    pub fn add_layer(
+        &mut self,
+        sprite_name: &str,
+        index: usize,
+        layer: SpriteLayer,
+        images: HashMap<FrameId, SpriteImageBuffer>,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            let layer_id = layer.common_data.name;
+
+            sprite.set_add_layer(index, layer);
+            for (frame, image) in images {
+                sprite_images.set_layer_image(frame, layer_id, image);
+            }
+            sprite_images.recomposite(sprite);
+
+            Ok(())
+        })
+    }
+
+    /// Removes a layer from a sprite, recomposites the sprite, and returns the layer.
+    /// The layer's images are removed on the next serialization.
+    pub fn remove_layer(
+        &mut self,
+        sprite_name: &str,
+        layer: LayerId,
+    ) -> Result<SpriteLayer, YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            let output = sprite.set_remove_layer(layer).ok_or_else(|| {
+                YyResourceHandlerError::BadEdit(
+                    "layer doesn't exist, or is the sprite's only layer".to_string(),
+                )
+            })?;
+
+            sprite_images.remove_layer(layer);
+            sprite_images.recomposite(sprite);
+
+            Ok(output)
+        })
+    }
+
+    /// Moves a layer of a sprite to the given index, where `0` is the top layer, and
+    /// recomposites the sprite.
+    pub fn move_layer(
+        &mut self,
+        sprite_name: &str,
+        layer: LayerId,
+        index: usize,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            if sprite.set_move_layer(layer, index) == false {
+                return Err(YyResourceHandlerError::BadEdit(
+                    "layer doesn't exist".to_string(),
+                ));
+            }
+            sprite_images.recomposite(sprite);
+
+            Ok(())
+        })
+    }
+
+    /// Sets the display name of a layer of a sprite.
+    pub fn rename_layer(
+        &mut self,
+        sprite_name: &str,
+        layer: LayerId,
+        display_name: &str,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, _| {
+            if sprite.set_rename_layer(layer, display_name) == false {
+                return Err(YyResourceHandlerError::BadEdit(
+                    "layer doesn't exist".to_string(),
+                ));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Inserts a new frame into a sprite at the given index, returning the id of the new frame.
+    ///
+    /// The image is given to the top layer of the new frame, and every other layer is left
+    /// transparent. It must be the same size as the sprite.
+    pub fn insert_frame(
+        &mut self,
+        sprite_name: &str,
+        index: usize,
+        image: SpriteImageBuffer,
+    ) -> Result<FrameId, YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            check_frame_dimensions(sprite, &image)?;
+
+            let frame_id = FrameId::new();
+            sprite.set_insert_frame(index, frame_id, SpriteSequenceId::new());
+
+            let first_layer = sprite.layers[0].common_data.name;
+            sprite_images.set_layer_image(frame_id, first_layer, image);
+            sprite_images.recomposite_frame(sprite, frame_id);
+
+            Ok(frame_id)
+        })
+    }
+
+    /// Removes a frame from a sprite. Its images are removed on the next serialization.
+    pub fn remove_frame(
+        &mut self,
+        sprite_name: &str,
+        frame: FrameId,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            if sprite.set_remove_frame(frame) == false {
+                return Err(YyResourceHandlerError::BadEdit(
+                    "frame doesn't exist".to_string(),
+                ));
+            }
+
+            sprite_images.frames.remove(&frame);
+            sprite_images.layers.remove(&frame);
+
+            Ok(())
+        })
+    }
+
+    /// Moves a frame of a sprite to the given index.
+    pub fn move_frame(
+        &mut self,
+        sprite_name: &str,
+        frame: FrameId,
+        index: usize,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, _| {
+            if sprite.set_move_frame(frame, index) == false {
+                return Err(YyResourceHandlerError::BadEdit(
+                    "frame doesn't exist".to_string(),
+                ));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Sets how long a frame of a sprite is shown for, in frames of the sprite's sequence.
+    /// Like [`SpriteExt::set_frame_length`], this can't touch broadcast messages.
+    pub fn set_frame_length(
+        &mut self,
+        sprite_name: &str,
+        frame: FrameId,
+        length: f64,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, _| {
+            if sprite.set_frame_length(frame, length) == false {
+                return Err(YyResourceHandlerError::BadEdit(
+                    "frame doesn't exist, or the length isn't positive".to_string(),
+                ));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Duplicates a frame of a sprite, with all of its layers, placing the copy directly
+    /// after the original. Returns the id of the copy.
+    pub fn duplicate_frame(
+        &mut self,
+        sprite_name: &str,
+        frame: FrameId,
+    ) -> Result<FrameId, YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            let index = sprite
+                .frames
+                .iter()
+                .position(|v| v.name == frame)
+                .ok_or_else(|| {
+                    YyResourceHandlerError::BadEdit("frame doesn't exist".to_string())
+                })?;
+
+            let frame_id = FrameId::new();
+            sprite.set_insert_frame(index + 1, frame_id, SpriteSequenceId::new());
+            if let Some(length) = sprite.frame_length(frame) {
+                sprite.set_frame_length(frame_id, length);
+            }
+
+            if let Some(image) = sprite_images.frames.get(&frame).cloned() {
+                sprite_images.frames.insert(frame_id, image);
+            }
+            if let Some(layers) = sprite_images.layers.get(&frame).cloned() {
+                sprite_images.layers.insert(frame_id, layers);
+            }
+
+            Ok(frame_id)
+        })
+    }
+
+    /// Crops, pads, resizes or changes the canvas of a sprite, transforming the image of every
+    /// frame and layer. See [`SpriteTransform`] for how the origin and bbox follow.
+    pub fn transform(
+        &mut self,
+        sprite_name: &str,
+        transform: SpriteTransform,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            transform
+                .apply(sprite, sprite_images)
+                .map_err(|e| YyResourceHandlerError::BadEdit(e.to_string()))?;
+
+            // scaling the layers and the composite separately won't quite match
+            sprite_images.recomposite(sprite);
+
+            Ok(())
+        })
+    }
+
+    /// Replaces the image of a frame of a sprite. The image must be the same size as the sprite.
+    ///
+    /// If a layer is given, only that layer's image is replaced, and the frame is recomposited.
+    /// Otherwise, the frame is flattened: the image is given to the top layer, and every
+    /// other layer is left transparent in that frame.
+    pub fn replace_frame_image(
+        &mut self,
+        sprite_name: &str,
+        frame: FrameId,
+        layer: Option<LayerId>,
+        image: SpriteImageBuffer,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(sprite_name, |sprite, sprite_images| {
+            check_frame_dimensions(sprite, &image)?;
+
+            if sprite.frames.iter().any(|v| v.name == frame) == false {
+                return Err(YyResourceHandlerError::BadEdit(
+                    "frame doesn't exist".to_string(),
+                ));
+            }
+
+            match layer {
+                Some(layer) => {
+                    if sprite.layers.iter().any(|v| v.common_data.name == layer) == false {
+                        return Err(YyResourceHandlerError::BadEdit(
+                            "layer doesn't exist".to_string(),
+                        ));
+                    }
+
+                    sprite_images.set_layer_image(frame, layer, image);
+                }
+                None => {
+                    let first_layer = sprite.layers[0].common_data.name;
+                    sprite_images
+                        .layers
+                        .insert(frame, maplit::hashmap! { first_layer => image });
+                }
+            }
+            sprite_images.recomposite_frame(sprite, frame);
+
+            Ok(())
+        })
+    }
+
+    /// Edits a sprite and its images, and marks it for serialization if the edit succeeds.
+    /// The sprite's associated data must be loaded.
+    fn edit_sprite<R>(
+        &mut self,
+        sprite_name: &str,
+        edit: impl FnOnce(&mut Sprite, &mut SpriteImages) -> Result<R, YyResourceHandlerError>,
+    ) -> Result<R, YyResourceHandlerError> {
+        // safety: we mark the sprite for serialization below
+        let data =
+            unsafe { self.get_mut(sprite_name) }.ok_or(YyResourceHandlerError::ResourceNotFound)?;
+        let images = data
+            .associated_data
+            .as_mut()
+            .ok_or(YyResourceHandlerError::AssociatedDataNotLoaded)?;
+
+        // layer edits change what a frame without layer images means, so we make them explicit
+        images.expand_single_layer_frames(&data.yy_resource);
+
+        let old = data.yy_resource.clone();
+        let output = edit(&mut data.yy_resource, images)?;
+
+        // an automatic bbox follows the images
+        if data.yy_resource.bbox_mode == BBoxMode::Automatic {
+            data.yy_resource.set_automatic_bbox(images);
+        }
+        self.mark_edited(sprite_name, &old)?;
+
+        Ok(output)
+    }
+}
+
+fn check_frame_dimensions(
+    sprite: &Sprite,
+    image: &SpriteImageBuffer,
+) -> Result<(), YyResourceHandlerError> {
+    let dimensions = (sprite.width.get() as u32, sprite.height.get() as u32);
+
+    if image.dimensions() != dimensions {
+        Err(YyResourceHandlerError::BadEdit(format!(
+            "image is {:?}, but the sprite is {:?}",
+            image.dimensions(),
+            dimensions
+        )))
+    } else {
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Bbox {
     pub top_left: (i32, i32),
@@ -505,3 +1114,92 @@ impl BboxModeUtility {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_order(sprite: &Sprite) -> Vec<(String, String, f64)> {
+        sprite
+            .frames
+            .iter()
+            .zip(sprite.sequence.tracks[0].keyframes.keyframes.iter())
+            .map(|(frame, kf)| {
+                (
+                    frame.name.inner().to_string(),
+                    kf.channels.zero.id.name.clone(),
+                    kf.key,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn frame_editing() {
+        let mut sprite = Sprite::new("spr_test", TexturePath::default(), ViewPath::default());
+        let frames: Vec<FrameId> = (0..3).map(|_| FrameId::new()).collect();
+        for frame in frames.iter() {
+            sprite.set_frame(*frame, SpriteSequenceId::new());
+        }
+
+        let new_frame = FrameId::new();
+        sprite.set_insert_frame(1, new_frame, SpriteSequenceId::new());
+        assert!(sprite.set_move_frame(frames[2], 0));
+        assert!(sprite.set_remove_frame(frames[0]));
+        assert!(sprite.set_remove_frame(frames[0]) == false);
+
+        let expected = [frames[2], new_frame, frames[1]];
+        let order = frame_order(&sprite);
+        assert_eq!(order.len(), 3);
+        assert_eq!(sprite.sequence.tracks[0].keyframes.keyframes.len(), 3);
+        for (i, (frame, keyframe, key)) in order.into_iter().enumerate() {
+            assert_eq!(frame, expected[i].inner().to_string());
+            assert_eq!(frame, keyframe);
+            assert_eq!(key, i as f64);
+        }
+        assert_eq!(sprite.sequence.length, 3.0);
+    }
+
+    #[test]
+    fn frame_lengths() {
+        let mut sprite = Sprite::new("spr_test", TexturePath::default(), ViewPath::default());
+        let frames: Vec<FrameId> = (0..3).map(|_| FrameId::new()).collect();
+        for frame in frames.iter() {
+            sprite.set_frame(*frame, SpriteSequenceId::new());
+        }
+
+        assert!(sprite.set_frame_length(frames[0], 2.5));
+        assert!(sprite.set_frame_length(frames[1], 0.0) == false);
+        assert!(sprite.set_frame_length(FrameId::new(), 1.0) == false);
+        assert_eq!(sprite.frame_length(frames[0]), Some(2.5));
+
+        let keys: Vec<f64> = frame_order(&sprite).into_iter().map(|v| v.2).collect();
+        assert_eq!(keys, vec![0.0, 2.5, 3.5]);
+        assert_eq!(sprite.sequence.length, 4.5);
+
+        assert!(sprite.set_move_frame(frames[0], 2));
+        assert_eq!(sprite.frame_length(frames[0]), Some(2.5));
+        assert_eq!(sprite.sequence.length, 4.5);
+    }
+
+    #[test]
+    fn empty_automatic_bbox() {
+        let frame = FrameId::new();
+        let mut sprite = Sprite::new("spr_test", TexturePath::default(), ViewPath::default())
+            .dimensions(NonZeroUsize::new(8).unwrap(), NonZeroUsize::new(4).unwrap());
+        sprite.set_frame(frame, SpriteSequenceId::new());
+        let images = SpriteImages::single_layer(
+            sprite.layers[0].common_data.name,
+            maplit::hashmap! { frame => SpriteImageBuffer::new(8, 4) },
+        );
+
+        // a transparent sprite's bbox covers the canvas, like a full image bbox
+        sprite.set_automatic_bbox(&images);
+        let (_, full_image) = BboxModeUtility::FullImage.to_bbox((8, 4));
+        assert_eq!(
+            (sprite.bbox_right, sprite.bbox_bottom),
+            full_image.bottom_right
+        );
+        assert_eq!(sprite.bbox_mode, BBoxMode::Automatic);
+    }
+}
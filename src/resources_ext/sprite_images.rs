@@ -0,0 +1,210 @@
+use super::SpriteImageBuffer;
+use image::Rgba;
+use std::collections::HashMap;
+use yy_typings::{BlendMode, FrameId, LayerId, Sprite, SpriteLayer};
+
+/// The associated data of a [`Sprite`]: the composite image of each frame, which is what
+/// GameMaker actually draws, and the image of each layer in each frame.
+///
+/// A layer which has no image in a frame is treated as fully transparent in that frame.
+/// A frame with no entry in `layers` at all is treated as a single layer sprite, where the
+/// composite *is* the image of the first layer. This is how images made outside of GameMaker
+/// are imported.
+///
+/// Layer images are not composited automatically -- after editing them, call [`recomposite`]
+/// or [`recomposite_frame`].
+///
+/// [`recomposite`]: #method.recomposite
+/// [`recomposite_frame`]: #method.recomposite_frame
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SpriteImages {
+    pub frames: HashMap<FrameId, SpriteImageBuffer>,
+    pub layers: HashMap<FrameId, HashMap<LayerId, SpriteImageBuffer>>,
+}
+
+impl SpriteImages {
+    /// Creates the images for a single layer sprite, where the composite of each frame is also
+    /// the image of its only layer.
+    pub fn single_layer(layer: LayerId, frames: HashMap<FrameId, SpriteImageBuffer>) -> Self {
+        let layers = frames
+            .iter()
+            .map(|(frame, image)| (*frame, maplit::hashmap! { layer => image.clone() }))
+            .collect();
+
+        Self { frames, layers }
+    }
+
+    /// Returns the image of a layer in a given frame, if it has one.
+    pub fn layer_image(&self, frame: FrameId, layer: LayerId) -> Option<&SpriteImageBuffer> {
+        self.layers.get(&frame).and_then(|v| v.get(&layer))
+    }
+
+    /// Sets the image of a layer in a given frame, returning the old image if there was one.
+    /// This does not recomposite the frame.
+    pub fn set_layer_image(
+        &mut self,
+        frame: FrameId,
+        layer: LayerId,
+        image: SpriteImageBuffer,
+    ) -> Option<SpriteImageBuffer> {
+        self.layers.entry(frame).or_default().insert(layer, image)
+    }
+
+    /// Gives every frame which has no entry in `layers` its composite as the image of the
+    /// sprite's first layer, so that it's no longer treated as a single layer frame.
+    pub fn expand_single_layer_frames(&mut self, sprite: &Sprite) {
+        let Some(first_layer) = sprite.layers.first() else {
+            return;
+        };
+
+        for (frame, image) in self.frames.iter() {
+            self.layers.entry(*frame).or_insert_with(
+                || maplit::hashmap! { first_layer.common_data.name => image.clone() },
+            );
+        }
+    }
+
+    /// Removes a layer's image from every frame. This does not recomposite the frames.
+    pub fn remove_layer(&mut self, layer: LayerId) {
+        for layers in self.layers.values_mut() {
+            layers.remove(&layer);
+        }
+    }
+
+    /// Composites the layers of a frame together, respecting each layer's visibility, opacity
+    /// and blend mode. Layers are drawn from the bottom of `sprite.layers` to the top, so the
+    /// first layer is drawn on top, as in the sprite editor.
+    ///
+    /// Returns `None` if the frame has no entry in `layers`, since its composite is then
+    /// its only source of truth.
+    pub fn composite(&self, sprite: &Sprite, frame: FrameId) -> Option<SpriteImageBuffer> {
+        let layers = self.layers.get(&frame)?;
+
+        let mut output =
+            SpriteImageBuffer::new(sprite.width.get() as u32, sprite.height.get() as u32);
+
+        for layer in sprite.layers.iter().rev() {
+            if layer.visible == false {
+                continue;
+            }
+
+            if let Some(image) = layers.get(&layer.common_data.name) {
+                blend_onto(
+                    &mut output,
+                    image,
+                    layer.opacity / 100.0,
+                    layer_blend_mode(layer),
+                );
+            }
+        }
+
+        Some(output)
+    }
+
+    /// Recomposites a single frame from its layers. If the frame has no entry in `layers`, its
+    /// composite is left alone.
+    pub fn recomposite_frame(&mut self, sprite: &Sprite, frame: FrameId) {
+        if let Some(composite) = self.composite(sprite, frame) {
+            self.frames.insert(frame, composite);
+        }
+    }
+
+    /// Recomposites every frame of the sprite from its layers.
+    pub fn recomposite(&mut self, sprite: &Sprite) {
+        for frame in sprite.frames.iter() {
+            self.recomposite_frame(sprite, frame.name);
+        }
+    }
+}
+
+/// The blend mode of the layer. GameMaker stores this as a number, so anything we don't
+/// recognize is treated as `Normal`.
+pub fn layer_blend_mode(layer: &SpriteLayer) -> BlendMode {
+    match layer.blend_mode {
+        1 => BlendMode::Add,
+        2 => BlendMode::Subtract,
+        3 => BlendMode::Multiply,
+        _ => BlendMode::Normal,
+    }
+}
+
+/// Blends `source` onto `dest`, with `opacity` between 0 and 1. The images are aligned at
+/// their top left corner, and any part of `source` outside of `dest` is ignored.
+pub fn blend_onto(
+    dest: &mut SpriteImageBuffer,
+    source: &SpriteImageBuffer,
+    opacity: f64,
+    blend_mode: BlendMode,
+) {
+    let opacity = opacity.clamp(0.0, 1.0) as f32;
+    let width = dest.width().min(source.width());
+    let height = dest.height().min(source.height());
+
+    for y in 0..height {
+        for x in 0..width {
+            let src = source.get_pixel(x, y);
+            let dst = dest.get_pixel_mut(x, y);
+            *dst = blend_pixel(*dst, *src, opacity, blend_mode);
+        }
+    }
+}
+
+/// Blends a single pixel, using the separable blend modes of the W3C compositing spec,
+/// followed by source-over compositing.
+fn blend_pixel(dest: Rgba<u8>, source: Rgba<u8>, opacity: f32, blend_mode: BlendMode) -> Rgba<u8> {
+    let src_alpha = source[3] as f32 / 255.0 * opacity;
+    if src_alpha <= 0.0 {
+        return dest;
+    }
+    let dst_alpha = dest[3] as f32 / 255.0;
+    let out_alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+
+    let mut output = [0; 4];
+    for i in 0..3 {
+        let cs = source[i] as f32 / 255.0;
+        let cb = dest[i] as f32 / 255.0;
+
+        let blended = match blend_mode {
+            BlendMode::Normal => cs,
+            BlendMode::Add => (cb + cs).min(1.0),
+            BlendMode::Subtract => (cb - cs).max(0.0),
+            BlendMode::Multiply => cb * cs,
+        };
+
+        let mixed = (1.0 - dst_alpha) * cs + dst_alpha * blended;
+        let premultiplied = src_alpha * mixed + dst_alpha * (1.0 - src_alpha) * cb;
+
+        output[i] = (premultiplied / out_alpha * 255.0).round() as u8;
+    }
+    output[3] = (out_alpha * 255.0).round() as u8;
+
+    Rgba(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blending() {
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        let clear = Rgba([0, 0, 0, 0]);
+
+        assert_eq!(blend_pixel(red, blue, 1.0, BlendMode::Normal), blue);
+        assert_eq!(blend_pixel(red, blue, 0.0, BlendMode::Normal), red);
+        assert_eq!(blend_pixel(clear, blue, 1.0, BlendMode::Multiply), blue);
+        assert_eq!(
+            blend_pixel(red, blue, 1.0, BlendMode::Add),
+            Rgba([255, 0, 255, 255])
+        );
+        assert_eq!(
+            blend_pixel(red, blue, 1.0, BlendMode::Multiply),
+            Rgba([0, 0, 0, 255])
+        );
+        assert_eq!(
+            blend_pixel(red, blue, 0.5, BlendMode::Normal),
+            Rgba([128, 0, 128, 255])
+        );
+    }
+}
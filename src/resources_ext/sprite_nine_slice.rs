@@ -0,0 +1,149 @@
+use thiserror::Error;
+use yy_typings::{GmEncodedColor, NineSlice, Sprite};
+
+/// The guides of a nine-slice sprite. As in GameMaker, each guide is measured in pixels
+/// inward from its own edge, so a `right` of `4` is four pixels in from the right edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub struct NineSliceGuides {
+    pub left: u64,
+    pub top: u64,
+    pub right: u64,
+    pub bottom: u64,
+}
+
+/// A region of a nine-slice sprite which has its own tile mode. The corners are never tiled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NineSliceRegion {
+    Left,
+    Top,
+    Right,
+    Bottom,
+    Center,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum NineSliceError {
+    #[error("left and right guides ({left} + {right}) don't fit in a sprite {width} pixels wide")]
+    GuidesTooWide { left: u64, right: u64, width: u64 },
+
+    #[error("top and bottom guides ({top} + {bottom}) don't fit in a sprite {height} pixels tall")]
+    GuidesTooTall { top: u64, bottom: u64, height: u64 },
+}
+
+impl NineSliceGuides {
+    /// Checks that the guides fit within the sprite without crossing each other.
+    pub fn validate(&self, sprite: &Sprite) -> Result<(), NineSliceError> {
+        let width = sprite.width.get() as u64;
+        let height = sprite.height.get() as u64;
+
+        if self.left + self.right > width {
+            return Err(NineSliceError::GuidesTooWide {
+                left: self.left,
+                right: self.right,
+                width,
+            });
+        }
+
+        if self.top + self.bottom > height {
+            return Err(NineSliceError::GuidesTooTall {
+                top: self.top,
+                bottom: self.bottom,
+                height,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The guides of a nine-slice.
+    pub fn from_nine_slice(nine_slice: &NineSlice) -> Self {
+        Self {
+            left: nine_slice.left,
+            top: nine_slice.top,
+            right: nine_slice.right,
+            bottom: nine_slice.bottom,
+        }
+    }
+}
+
+impl NineSliceRegion {
+    /// The index of the region in `NineSlice::tile_mode`.
+    pub fn index(self) -> usize {
+        match self {
+            NineSliceRegion::Left => 0,
+            NineSliceRegion::Top => 1,
+            NineSliceRegion::Right => 2,
+            NineSliceRegion::Bottom => 3,
+            NineSliceRegion::Center => 4,
+        }
+    }
+}
+
+/// A disabled nine-slice with no guides, using the guide and highlight colors GameMaker
+/// gives a new nine-slice.
+pub fn default_nine_slice() -> NineSlice {
+    // `GmEncodedColor` can only be made through serde.
+    let color = |v: u64| -> GmEncodedColor { serde_json::from_value(v.into()).unwrap() };
+
+    NineSlice {
+        guide_colour: [
+            color(4294902015),
+            color(4294902015),
+            color(4294902015),
+            color(4294902015),
+        ],
+        highlight_colour: color(1728023040),
+        ..NineSlice::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpriteExt;
+    use std::num::NonZeroUsize;
+    use yy_typings::{TexturePath, TileMode, ViewPath};
+
+    #[test]
+    fn guides() {
+        let sprite = Sprite::new("spr_panel", TexturePath::default(), ViewPath::default())
+            .dimensions(
+                NonZeroUsize::new(16).unwrap(),
+                NonZeroUsize::new(8).unwrap(),
+            );
+
+        let guides = NineSliceGuides {
+            left: 4,
+            top: 2,
+            right: 12,
+            bottom: 6,
+        };
+        assert_eq!(guides.validate(&sprite), Ok(()));
+        assert!(matches!(
+            NineSliceGuides {
+                right: 13,
+                ..guides
+            }
+            .validate(&sprite),
+            Err(NineSliceError::GuidesTooWide { .. })
+        ));
+        assert!(matches!(
+            NineSliceGuides { top: 3, ..guides }.validate(&sprite),
+            Err(NineSliceError::GuidesTooTall { .. })
+        ));
+
+        let sprite = sprite
+            .nine_slice(true)
+            .nine_slice_guides(guides)
+            .unwrap()
+            .nine_slice_tile_mode(NineSliceRegion::Center, TileMode::Repeat);
+        let nine_slice = sprite.nine_slice.as_ref().unwrap();
+        assert!(nine_slice.enabled);
+        assert_eq!(NineSliceGuides::from_nine_slice(nine_slice), guides);
+        assert_eq!(nine_slice.tile_mode[4], TileMode::Repeat);
+        assert_eq!(nine_slice.tile_mode[0], TileMode::Stretch);
+
+        let sprite = sprite.nine_slice(false);
+        assert!(sprite.nine_slice.as_ref().unwrap().enabled == false);
+    }
+}
@@ -0,0 +1,256 @@
+use super::{SpriteExt, SpriteImageBuffer, SpriteImages};
+use image::GenericImageView;
+use std::{collections::HashMap, num::NonZeroUsize};
+use thiserror::Error;
+use yy_typings::{FrameId, Sprite, SpriteSequenceId, TexturePath, ViewPath};
+
+/// How frames are laid out in a sprite sheet. Frames are read left to right, then top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SheetGrid {
+    pub columns: u32,
+    pub rows: u32,
+    pub frame_width: u32,
+    pub frame_height: u32,
+
+    /// The position of the top left corner of the first frame in the sheet.
+    pub offset: (u32, u32),
+
+    /// The horizontal and vertical gap between frames.
+    pub separation: (u32, u32),
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SpriteSheetError {
+    #[error("sheet grid must have at least one frame, and frames cannot be empty")]
+    EmptyGrid,
+
+    #[error("frame {index} is at {position:?}, which is outside of the {sheet:?} sheet")]
+    FrameOutOfBounds {
+        index: u32,
+        position: (u32, u32),
+        sheet: (u32, u32),
+    },
+
+    #[error("{0:?} is not named like a strip, such as `spr_walk_strip8`")]
+    NotAStrip(String),
+
+    #[error("a sheet {width} pixels wide can't be cut into {frames} frames")]
+    BadStripWidth { width: u32, frames: u32 },
+
+    #[error("frames must all be the same size -- expected {expected:?}, found {found:?}")]
+    MismatchedFrames {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+
+    #[error("couldn't read sheet image -- {0}")]
+    BadImage(String),
+}
+
+impl SheetGrid {
+    /// A grid of a single row of frames, with no offset or separation.
+    pub fn strip(
+        frames: u32,
+        sheet_width: u32,
+        sheet_height: u32,
+    ) -> Result<Self, SpriteSheetError> {
+        if frames == 0 || sheet_width.is_multiple_of(frames) == false {
+            return Err(SpriteSheetError::BadStripWidth {
+                width: sheet_width,
+                frames,
+            });
+        }
+
+        Ok(Self {
+            columns: frames,
+            rows: 1,
+            frame_width: sheet_width / frames,
+            frame_height: sheet_height,
+            offset: (0, 0),
+            separation: (0, 0),
+        })
+    }
+
+    /// Cuts the sheet into frames. Every frame must be entirely within the sheet.
+    pub fn slice(
+        &self,
+        sheet: &SpriteImageBuffer,
+    ) -> Result<Vec<SpriteImageBuffer>, SpriteSheetError> {
+        if self.columns == 0 || self.rows == 0 || self.frame_width == 0 || self.frame_height == 0 {
+            return Err(SpriteSheetError::EmptyGrid);
+        }
+
+        let (width, height) = sheet.dimensions();
+        let x = |column| grid_position(self.offset.0, column, self.frame_width, self.separation.0);
+        let y = |row| grid_position(self.offset.1, row, self.frame_height, self.separation.1);
+        let fits = |start: Option<u32>, size: u32, sheet_size: u32| {
+            start
+                .and_then(|v| v.checked_add(size))
+                .is_some_and(|end| end <= sheet_size)
+        };
+
+        // we check every frame fits before allocating anything. Frames can't overlap, so this
+        // looks at no more than a sheet's width of columns, and a sheet's height of rows.
+        let bad_column =
+            (0..self.columns).find(|&column| fits(x(column), self.frame_width, width) == false);
+        let bad_row = (0..self.rows).find(|&row| fits(y(row), self.frame_height, height) == false);
+        let bad_frame = match (bad_column, bad_row) {
+            (_, Some(0)) => Some((0, 0)),
+            (Some(column), _) => Some((column, 0)),
+            (None, Some(row)) => Some((0, row)),
+            (None, None) => None,
+        };
+        if let Some((column, row)) = bad_frame {
+            return Err(SpriteSheetError::FrameOutOfBounds {
+                index: row.saturating_mul(self.columns).saturating_add(column),
+                position: (x(column).unwrap_or(u32::MAX), y(row).unwrap_or(u32::MAX)),
+                sheet: (width, height),
+            });
+        }
+
+        let mut output = Vec::with_capacity(self.columns as usize * self.rows as usize);
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                // we checked above that every frame fits
+                let (x, y) = (x(column).unwrap(), y(row).unwrap());
+
+                output.push(
+                    sheet
+                        .view(x, y, self.frame_width, self.frame_height)
+                        .to_image(),
+                );
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// The position of the frame at `index` along one axis of a grid, or `None` if it overflows.
+fn grid_position(offset: u32, index: u32, size: u32, separation: u32) -> Option<u32> {
+    if index == 0 {
+        return Some(offset);
+    }
+
+    size.checked_add(separation)?
+        .checked_mul(index)?
+        .checked_add(offset)
+}
+
+/// Splits a name which follows GameMaker's strip convention, such as `spr_walk_strip8`,
+/// into the name of the sprite and its number of frames, such as `("spr_walk", 8)`.
+pub fn parse_strip_name(name: &str) -> Option<(&str, u32)> {
+    let (name, frames) = name.rsplit_once("_strip")?;
+    let frames = frames.parse().ok().filter(|v| *v > 0)?;
+
+    if name.is_empty() {
+        None
+    } else {
+        Some((name, frames))
+    }
+}
+
+/// Creates a new single layer sprite out of the given frames, which must all be the same size.
+/// The sprite gets an automatic bbox, and is otherwise the same as [`SpriteExt::new`].
+pub fn sprite_from_frames(
+    name: &str,
+    texture_group_id: TexturePath,
+    parent: ViewPath,
+    frames: Vec<SpriteImageBuffer>,
+) -> Result<(Sprite, SpriteImages), SpriteSheetError> {
+    let dimensions = frames
+        .first()
+        .ok_or(SpriteSheetError::EmptyGrid)?
+        .dimensions();
+    if let Some(bad) = frames.iter().find(|v| v.dimensions() != dimensions) {
+        return Err(SpriteSheetError::MismatchedFrames {
+            expected: dimensions,
+            found: bad.dimensions(),
+        });
+    }
+
+    let width = NonZeroUsize::new(dimensions.0 as usize).ok_or(SpriteSheetError::EmptyGrid)?;
+    let height = NonZeroUsize::new(dimensions.1 as usize).ok_or(SpriteSheetError::EmptyGrid)?;
+
+    let mut sprite = Sprite::new(name, texture_group_id, parent).dimensions(width, height);
+    let mut images = HashMap::with_capacity(frames.len());
+    for image in frames {
+        let frame_id = FrameId::new();
+        sprite.set_frame(frame_id, SpriteSequenceId::new());
+        images.insert(frame_id, image);
+    }
+
+    let images = SpriteImages::single_layer(sprite.layers[0].common_data.name, images);
+    sprite.set_automatic_bbox(&images);
+
+    Ok((sprite, images))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn strip_names() {
+        assert_eq!(parse_strip_name("spr_walk_strip8"), Some(("spr_walk", 8)));
+        assert_eq!(parse_strip_name("walk_strip12"), Some(("walk", 12)));
+        assert_eq!(parse_strip_name("spr_walk"), None);
+        assert_eq!(parse_strip_name("spr_walk_strip"), None);
+        assert_eq!(parse_strip_name("spr_walk_strip0"), None);
+        assert_eq!(parse_strip_name("_strip4"), None);
+    }
+
+    #[test]
+    fn slicing() {
+        let mut sheet = SpriteImageBuffer::new(11, 5);
+        sheet.put_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        sheet.put_pixel(6, 1, Rgba([0, 255, 0, 255]));
+
+        let grid = SheetGrid {
+            columns: 2,
+            rows: 1,
+            frame_width: 4,
+            frame_height: 4,
+            offset: (1, 1),
+            separation: (1, 0),
+        };
+        let frames = grid.slice(&sheet).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(frames[1].get_pixel(0, 0), &Rgba([0, 255, 0, 255]));
+
+        let too_big = SheetGrid { columns: 3, ..grid };
+        assert!(matches!(
+            too_big.slice(&sheet),
+            Err(SpriteSheetError::FrameOutOfBounds { index: 2, .. })
+        ));
+
+        // huge grids are out of bounds, rather than overflowing or allocating
+        let huge = SheetGrid {
+            columns: u32::MAX,
+            rows: u32::MAX,
+            ..grid
+        };
+        assert!(matches!(
+            huge.slice(&sheet),
+            Err(SpriteSheetError::FrameOutOfBounds { index: 2, .. })
+        ));
+        let far = SheetGrid {
+            rows: 2,
+            separation: (1, u32::MAX),
+            ..grid
+        };
+        assert!(matches!(
+            far.slice(&sheet),
+            Err(SpriteSheetError::FrameOutOfBounds {
+                index: 2,
+                position: (1, u32::MAX),
+                ..
+            })
+        ));
+
+        assert_eq!(SheetGrid::strip(4, 32, 8).unwrap().frame_width, 8);
+        assert!(SheetGrid::strip(3, 32, 8).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{Bbox, SpriteImageBuffer, SpriteImages};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use yy_typings::{FrameId, Sprite, TextureGroup};
+
+/// How a texture group's frames are packed onto texture pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackingSettings {
+    pub page_size: (u32, u32),
+
+    /// Empty pixels kept around each frame on the page.
+    pub border: u32,
+
+    /// Whether frames are cropped to their visible pixels before being packed.
+    pub autocrop: bool,
+}
+
+/// The result of simulating how a texture group would be packed. This is an estimate --
+/// GameMaker's own packer is better at filling pages, so real builds may use fewer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackingReport {
+    pub settings: PackingSettings,
+    pub pages: Vec<TexturePage>,
+
+    /// How much of the pages' area is taken up by frames and their borders, between 0 and 1.
+    pub fill_ratio: f64,
+
+    /// Sprites with a frame which is bigger than a page, and so can't be packed. GameMaker
+    /// scales these down to fit when building.
+    pub oversized: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TexturePage {
+    pub items: Vec<PackedFrame>,
+}
+
+/// A frame placed on a texture page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedFrame {
+    pub sprite: String,
+    pub frame: FrameId,
+
+    /// The top left corner of the frame's image on the page, inside of its border.
+    pub position: (u32, u32),
+
+    /// The top left corner of the part of the frame which was packed, which is only
+    /// not `(0, 0)` if the frame was autocropped.
+    pub source: (u32, u32),
+    pub size: (u32, u32),
+}
+
+#[derive(Debug, Error)]
+pub enum TexturePackingError {
+    #[error("couldn't find texture group {0}")]
+    NoTextureGroup(String),
+
+    #[error("couldn't load images of sprite {sprite} -- {error}")]
+    CouldNotLoadSprite { sprite: String, error: String },
+
+    #[error("couldn't write texture page -- {0}")]
+    CouldNotWritePage(String),
+}
+
+impl PackingSettings {
+    /// GameMaker's default texture page size.
+    pub const DEFAULT_PAGE_SIZE: (u32, u32) = (2048, 2048);
+
+    /// The settings of a texture group, packed onto pages of the given size.
+    pub fn from_texture_group(texture_group: &TextureGroup, page_size: (u32, u32)) -> Self {
+        Self {
+            page_size,
+            border: texture_group.border as u32,
+            autocrop: texture_group.autocrop,
+        }
+    }
+}
+
+/// A page being filled, shelf by shelf. Each shelf is a row as tall as the first item put
+/// on it, and items are added to it left to right.
+#[derive(Default)]
+struct OpenPage {
+    page: TexturePage,
+
+    /// The y position, height and filled width of each shelf.
+    shelves: Vec<(u32, u32, u32)>,
+}
+
+impl OpenPage {
+    /// Finds room for an item, including its border, on an existing shelf or a new one.
+    fn place(&mut self, size: (u32, u32), page_size: (u32, u32)) -> Option<(u32, u32)> {
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .find(|(_, height, width)| size.1 <= *height && width + size.0 <= page_size.0);
+
+        if let Some((y, _, width)) = shelf {
+            let output = (*width, *y);
+            *width += size.0;
+            return Some(output);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map(|(y, height, _)| y + height)
+            .unwrap_or_default();
+        if y + size.1 > page_size.1 || size.0 > page_size.0 {
+            return None;
+        }
+        self.shelves.push((y, size.1, size.0));
+
+        Some((0, y))
+    }
+}
+
+/// Simulates packing every frame of the given sprites onto texture pages, using a simple
+/// shelf packer with frames sorted tallest first.
+pub fn pack_sprites(
+    sprites: &[(&Sprite, &SpriteImages)],
+    settings: PackingSettings,
+) -> PackingReport {
+    let border = settings.border;
+    let mut oversized = vec![];
+    let mut items = vec![];
+
+    for (sprite, images) in sprites {
+        let name = &sprite.common_data.name;
+
+        for frame in sprite.frames.iter() {
+            let Some(image) = images.frames.get(&frame.name) else {
+                continue;
+            };
+
+            let (source, size) = visible_area(image, settings.autocrop);
+            if size.0 + border * 2 > settings.page_size.0
+                || size.1 + border * 2 > settings.page_size.1
+            {
+                if oversized.contains(name) == false {
+                    oversized.push(name.clone());
+                }
+                continue;
+            }
+
+            items.push((name.clone(), frame.name, source, size));
+        }
+    }
+
+    items.sort_by_key(|(_, _, _, size)| std::cmp::Reverse((size.1, size.0)));
+
+    let mut pages: Vec<OpenPage> = vec![];
+    let mut used_area = 0;
+    for (sprite, frame, source, size) in items {
+        let padded = (size.0 + border * 2, size.1 + border * 2);
+        used_area += padded.0 as u64 * padded.1 as u64;
+
+        let placed = pages.iter_mut().find_map(|page| {
+            let position = page.place(padded, settings.page_size)?;
+            Some((page, position))
+        });
+        let (page, position) = match placed {
+            Some(placed) => placed,
+            None => {
+                pages.push(OpenPage::default());
+                let page = pages.last_mut().unwrap();
+                let position = page.place(padded, settings.page_size).unwrap();
+                (page, position)
+            }
+        };
+
+        page.page.items.push(PackedFrame {
+            sprite,
+            frame,
+            position: (position.0 + border, position.1 + border),
+            source,
+            size,
+        });
+    }
+
+    let page_area = settings.page_size.0 as u64 * settings.page_size.1 as u64;
+    let fill_ratio = if pages.is_empty() {
+        0.0
+    } else {
+        used_area as f64 / (page_area * pages.len() as u64) as f64
+    };
+
+    PackingReport {
+        settings,
+        pages: pages.into_iter().map(|v| v.page).collect(),
+        fill_ratio,
+        oversized,
+    }
+}
+
+impl PackingReport {
+    /// Draws each simulated page, using the same sprites which were packed.
+    pub fn render_pages(&self, sprites: &[(&Sprite, &SpriteImages)]) -> Vec<SpriteImageBuffer> {
+        self.pages
+            .iter()
+            .map(|page| {
+                let mut output =
+                    SpriteImageBuffer::new(self.settings.page_size.0, self.settings.page_size.1);
+
+                for item in page.items.iter() {
+                    let image = sprites
+                        .iter()
+                        .find(|(sprite, _)| sprite.common_data.name == item.sprite)
+                        .and_then(|(_, images)| images.frames.get(&item.frame));
+
+                    if let Some(image) = image {
+                        let source = image::imageops::crop_imm(
+                            image,
+                            item.source.0,
+                            item.source.1,
+                            item.size.0,
+                            item.size.1,
+                        )
+                        .to_image();
+                        image::imageops::replace(
+                            &mut output,
+                            &source,
+                            item.position.0,
+                            item.position.1,
+                        );
+                    }
+                }
+
+                output
+            })
+            .collect()
+    }
+}
+
+/// The part of a frame which gets packed. A fully transparent frame still takes up a pixel.
+fn visible_area(image: &SpriteImageBuffer, autocrop: bool) -> ((u32, u32), (u32, u32)) {
+    if autocrop == false {
+        return ((0, 0), image.dimensions());
+    }
+
+    match Bbox::from_alpha(image, 0) {
+        Some(bbox) => (
+            (bbox.top_left.0 as u32, bbox.top_left.1 as u32),
+            (
+                (bbox.bottom_right.0 - bbox.top_left.0 + 1) as u32,
+                (bbox.bottom_right.1 - bbox.top_left.1 + 1) as u32,
+            ),
+        ),
+        None => ((0, 0), (1, 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite_from_frames;
+    use image::Rgba;
+    use yy_typings::{TexturePath, ViewPath};
+
+    fn sprite(name: &str, size: u32, frames: usize) -> (Sprite, SpriteImages) {
+        let frames = (0..frames)
+            .map(|_| {
+                let mut image = SpriteImageBuffer::new(size + 2, size + 2);
+                for x in 1..=size {
+                    for y in 1..=size {
+                        image.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                    }
+                }
+                image
+            })
+            .collect();
+
+        sprite_from_frames(name, TexturePath::default(), ViewPath::default(), frames).unwrap()
+    }
+
+    #[test]
+    fn packing() {
+        let big = sprite("spr_big", 10, 2);
+        let small = sprite("spr_small", 4, 3);
+        let huge = sprite("spr_huge", 40, 1);
+        let sprites = [(&big.0, &big.1), (&small.0, &small.1), (&huge.0, &huge.1)];
+
+        let settings = PackingSettings {
+            page_size: (20, 20),
+            border: 1,
+            autocrop: true,
+        };
+        let report = pack_sprites(&sprites, settings);
+
+        assert_eq!(report.oversized, vec!["spr_huge".to_string()]);
+        // each big frame needs a page of its own, and the small ones fit around the first
+        assert_eq!(report.pages.len(), 2);
+        assert_eq!(report.pages[0].items[0].source, (1, 1));
+        assert_eq!(report.pages[0].items[0].position, (1, 1));
+        let expected_fill = (2 * 12 * 12 + 3 * 6 * 6) as f64 / (2 * 20 * 20) as f64;
+        assert!((report.fill_ratio - expected_fill).abs() < 1e-9);
+
+        let pages = report.render_pages(&sprites);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].get_pixel(1, 1), &Rgba([255, 255, 255, 255]));
+        assert_eq!(pages[0].get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+    }
+}
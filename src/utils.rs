@@ -12,8 +12,11 @@ pub enum FileSerializationError {
     Io(String),
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize,
+)]
 pub enum SerializationFormat {
+    #[default]
     Json,
     Yaml,
 }
@@ -60,12 +63,6 @@ impl SerializationFormat {
     }
 }
 
-impl Default for SerializationFormat {
-    fn default() -> Self {
-        Self::Json
-    }
-}
-
 pub fn serialize_json(
     absolute_path: &Path,
     data: &impl serde::Serialize,
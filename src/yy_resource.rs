@@ -7,9 +7,9 @@ use std::{
 use yy_typings::{FilesystemPath, TrailingCommaUtility, ViewPath};
 
 pub trait YyResource:
-    Serialize + for<'de> Deserialize<'de> + Clone + Default + PartialEq + 'static
+    Serialize + for<'de> Deserialize<'de> + Clone + Default + PartialEq + Send + Sync + 'static
 {
-    type AssociatedData: Debug + Clone + PartialEq + Default;
+    type AssociatedData: Debug + Clone + PartialEq + Default + Send + Sync;
     const SUBPATH_NAME: &'static str;
     const RESOURCE: Resource;
 
@@ -101,8 +101,10 @@ pub trait YyResource:
     /// This functions is used to clean up those files. All of the paths are relative to the directory
     /// of the yy file.
     ///
-    /// This function is ONLY called when a resource is being replaced. When a resource is being removed
-    /// outright, then the entire folder is removed, so we don't need to carefully handle this.
+    /// This function is ONLY called when a resource is being replaced or edited. When a resource is being
+    /// removed outright, then the entire folder is removed, so we don't need to carefully handle this.
+    /// After an edit, only the files which the old resource lists here, but the edited one doesn't,
+    /// are removed, so list files individually if an edit can make just some of them obsolete.
     fn cleanup_on_replace(&self, paths_to_delete: impl FileHolder);
 }
 
@@ -169,3 +171,9 @@ impl From<serde_json::Error> for SerializedDataError {
 pub trait FileHolder {
     fn push(&mut self, f: PathBuf);
 }
+
+impl FileHolder for &mut Vec<PathBuf> {
+    fn push(&mut self, f: PathBuf) {
+        Vec::push(self, f)
+    }
+}
@@ -1,11 +1,12 @@
 use super::{
     directory_manager::DirectoryManager,
-    dirty_handler::{DirtyDrain, DirtyHandler},
+    dirty_handler::{DirtyDrain, DirtyHandler, DirtyState},
     YyResource,
 };
-use crate::YyResourceHandlerError;
+use crate::{FileHolder, SerializationFailure, YyResourceHandlerError};
 use anyhow::Result as AnyResult;
 use log::{error, info};
+use rayon::prelude::*;
 use std::{
     collections::HashMap,
     fs,
@@ -115,6 +116,39 @@ impl<T: YyResource> YyResourceHandler<T> {
         }
     }
 
+    /// Marks a resource for serialization after an edit, where `old` is the resource before
+    /// the edit. Every file which `old` would clean up on a replace, but the edited resource
+    /// wouldn't, is removed when it is serialized.
+    ///
+    /// This is for edits which make some associated files obsolete, such as removing a frame
+    /// from a sprite.
+    pub(crate) fn mark_edited(
+        &mut self,
+        name: &str,
+        old: &T,
+    ) -> Result<(), YyResourceHandlerError> {
+        let new = &self
+            .resources
+            .get(name)
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?
+            .yy_resource;
+
+        let mut new_files = vec![];
+        new.cleanup_on_replace(&mut new_files);
+        let mut obsolete_files = vec![];
+        old.cleanup_on_replace(&mut obsolete_files);
+        obsolete_files.retain(|path| new_files.contains(path) == false);
+
+        self.dirty_handler
+            .edit_associated(name.to_string(), |mut files| {
+                for path in obsolete_files {
+                    files.push(path);
+                }
+            });
+
+        Ok(())
+    }
+
     pub(crate) fn edit_parent(
         &mut self,
         name: &str,
@@ -244,19 +278,31 @@ impl<T: YyResource> YyResourceHandler<T> {
         }
     }
 
-    /// Loads the resource in on startup. We don't track associated data by default,
-    /// and we don't mark the resource as dirty.
-    pub(crate) fn load_on_startup(&mut self, value: T) {
-        self.insert_resource(value, None);
+    /// Loads the resource in on startup, with its associated data if it was requested.
+    /// We don't mark the resource as dirty.
+    pub(crate) fn load_on_startup(&mut self, value: T, associated_data: Option<T::AssociatedData>) {
+        self.insert_resource(value, associated_data);
     }
 
     /// Writes all of the resources to disk, and cleans up excess files.
-    pub(crate) fn serialize(&mut self, directory_manager: &DirectoryManager) -> AnyResult<()> {
+    ///
+    /// Resources are written in parallel. We don't stop at the first failure: every resource
+    /// which couldn't be removed or written is returned, sorted by name, and stays marked
+    /// for serialization.
+    pub(crate) fn serialize(
+        &mut self,
+        directory_manager: &DirectoryManager,
+    ) -> Vec<SerializationFailure> {
         let DirtyDrain {
             resources_to_remove,
             resources_to_reserialize,
             associated_values,
         } = self.dirty_handler.drain_all();
+        let resources_to_remove: Vec<String> = resources_to_remove.map(|(name, _)| name).collect();
+        let resources_to_reserialize: Vec<(String, DirtyState)> =
+            resources_to_reserialize.collect();
+        let associated_values: Option<Vec<(String, Vec<PathBuf>)>> =
+            associated_values.map(|v| v.collect());
 
         // Remove files or folders...
         if let Some(ass_values) = associated_values {
@@ -276,7 +322,7 @@ impl<T: YyResource> YyResourceHandler<T> {
                                 error!("couldn't remove folder {:#?}, {:#?}", path, e);
                             }
                         }
-                    } else {
+                    } else if path.exists() {
                         match fs::remove_file(&path) {
                             Ok(()) => {
                                 info!("removed file {:?}", path);
@@ -290,41 +336,82 @@ impl<T: YyResource> YyResourceHandler<T> {
             }
         }
 
+        // any resource which fails is marked dirty again, so the next serialization retries it
+        let mut failures = vec![];
+
         // Removes the resources!
-        for (resource_to_remove, _) in resources_to_remove {
+        for resource_to_remove in resources_to_remove {
             let path = FilesystemPath::new_path(T::SUBPATH_NAME, &resource_to_remove);
             let yy_path = directory_manager.resource_file(&path);
-            fs::remove_dir_all(yy_path.parent().unwrap())?;
+
+            if let Err(e) = fs::remove_dir_all(yy_path.parent().unwrap()) {
+                self.dirty_handler.remove(&resource_to_remove);
+                failures.push(SerializationFailure {
+                    name: resource_to_remove,
+                    resource: T::RESOURCE,
+                    error: e.into(),
+                });
+            }
         }
 
         // Finally, reserialize resources
-        for (resource_to_reserialize, _) in resources_to_reserialize {
-            let resource = self
-                .resources
-                .get(&resource_to_reserialize)
-                .expect("This should always be valid.");
+        let resources = &self.resources;
+        let reserialize_failures: Vec<(SerializationFailure, DirtyState)> =
+            resources_to_reserialize
+                .into_par_iter()
+                .filter_map(|(name, dirty_state)| {
+                    let resource = resources.get(&name).expect("This should always be valid.");
+
+                    Self::serialize_resource(resource, directory_manager)
+                        .err()
+                        .map(|error| {
+                            let failure = SerializationFailure {
+                                name,
+                                resource: T::RESOURCE,
+                                error,
+                            };
+
+                            (failure, dirty_state)
+                        })
+                })
+                .collect();
+
+        for (failure, dirty_state) in reserialize_failures {
+            match dirty_state {
+                DirtyState::New => self.dirty_handler.add(failure.name.clone()),
+                DirtyState::Edit => self.dirty_handler.edit(failure.name.clone()),
+            }
+            failures.push(failure);
+        }
 
-            let yy_path =
-                directory_manager.resource_file(&resource.yy_resource.relative_yy_filepath());
+        failures.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+        failures
+    }
 
-            let parent_dir = yy_path.parent().expect("impossible");
-            fs::create_dir_all(parent_dir)?;
+    /// Writes a single resource, and its associated data, to disk.
+    fn serialize_resource(
+        resource: &YyResourceData<T>,
+        directory_manager: &DirectoryManager,
+    ) -> AnyResult<()> {
+        let yy_path = directory_manager.resource_file(&resource.yy_resource.relative_yy_filepath());
 
-            if let Some(associated_data) = &resource.associated_data {
-                resource
-                    .yy_resource
-                    .serialize_associated_data(parent_dir, associated_data)?;
-            } else {
-                error!(
-                    "{} {} was marked for serialization but we didn't have its associated data",
-                    T::RESOURCE,
-                    resource.yy_resource.name()
-                )
-            }
-            let output_string = yy_typings::serialize_file(&resource.yy_resource);
-            std::fs::write(yy_path, output_string)
-                .map_err(|e| crate::FileSerializationError::Io(e.to_string()))?;
+        let parent_dir = yy_path.parent().expect("impossible");
+        fs::create_dir_all(parent_dir)?;
+
+        if let Some(associated_data) = &resource.associated_data {
+            resource
+                .yy_resource
+                .serialize_associated_data(parent_dir, associated_data)?;
+        } else {
+            error!(
+                "{} {} was marked for serialization but we didn't have its associated data",
+                T::RESOURCE,
+                resource.yy_resource.name()
+            )
         }
+        let output_string = yy_typings::serialize_file(&resource.yy_resource);
+        std::fs::write(yy_path, output_string)
+            .map_err(|e| crate::FileSerializationError::Io(e.to_string()))?;
 
         Ok(())
     }
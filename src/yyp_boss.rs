@@ -1,9 +1,24 @@
 use crate::{
-    directory_manager::DirectoryManager, errors::*, folders::*, utils, FileSerializationError,
-    ProjectMetadata, Resource, YyResource, YyResourceData, YyResourceHandler,
+    directory_manager::DirectoryManager,
+    errors::*,
+    find_duplicates, find_identifier, find_pattern,
+    folders::*,
+    load_cache::{CacheEntry, LoadCache},
+    pack_sprites, parse_strip_name, rename_identifier, sprite_from_frames, sprite_gif,
+    sprite_sheet, utils, AsepriteError, AsepriteFile, AsepriteTags, DuplicateReport,
+    FileSerializationError, GmlMatch, GmlSource, GmlUsage, IdeVersion, LoadFailure, LoadOptions,
+    LoadReport, PackingReport, PackingSettings, Placeholder, PlaceholderHandler, PlainGmlHandler,
+    ProjectMetadata, Resource, SheetAtlas, SheetGrid, SpriteExportError, SpriteImages,
+    SpriteSheetError, SymbolTable, TexturePackingError, VersionPolicy, YyResource, YyResourceData,
+    YyResourceHandler,
 };
 use anyhow::Result as AnyResult;
-use std::{fs, path::Path};
+use rayon::prelude::*;
+use regex::Regex;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use yy_typings::{
     AnimationCurve, Extension, Font, Note, Object, Path as YyPath, ResourceNameValidator, Room,
     Script, Sequence, Shader, Sound, Sprite, TexturePath, TileSet, Timeline, TrailingCommaUtility,
@@ -35,96 +50,265 @@ pub struct YypBoss {
     pub tilesets: YyResourceHandler<TileSet>,
     pub timelines: YyResourceHandler<Timeline>,
 
+    /// Resources which failed to load in a lenient load. See [`LoadOptions::lenient`].
+    pub placeholders: PlaceholderHandler,
+
+    /// The GML of rooms and timelines, which is read when the project's GML is first used.
+    pub plain_gml: PlainGmlHandler,
+
     pub vfs: Vfs,
     yyp: Yyp,
 }
 
 impl YypBoss {
+    /// Loads the project at the given path, loading the associated data for every resource
+    /// kind in `resources_to_scan`. This aborts on the first resource which fails to load.
+    ///
+    /// For lenient loading, see [`with_options`].
+    ///
+    /// [`with_options`]: #method.with_options
     pub fn new<P: AsRef<Path>>(
         path_to_yyp: P,
         resources_to_scan: &[Resource],
     ) -> Result<YypBoss, StartupError> {
-        let mut yyp_boss = Self::without_resources(path_to_yyp)?;
-
-        // load in all of our resources...
-        for yyp_resource in yyp_boss.yyp.resources.clone().into_iter() {
-            let path_as_str = yyp_resource.id.path.to_string_lossy();
-
-            let subpath = path_as_str
-                .split('/')
-                .next()
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
-
-            let resource = Resource::parse_subpath(subpath)
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
-            let assoc = resources_to_scan.contains(&resource);
-
-            match resource {
-                Resource::Sprite => load_in_file::<Sprite>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Script => load_in_file::<Script>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Object => load_in_file::<Object>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Note => load_in_file::<Note>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Shader => load_in_file::<Shader>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::AnimationCurve => {
-                    load_in_file::<AnimationCurve>(yyp_resource, &mut yyp_boss, assoc)
+        let options = LoadOptions {
+            associated_data: resources_to_scan.to_vec(),
+            ..LoadOptions::default()
+        };
+
+        Self::with_options(path_to_yyp, &options).map(|(yyp_boss, _)| yyp_boss)
+    }
+
+    /// Loads the project at the given path with the given options, returning the project
+    /// and a report of every resource which failed to load.
+    ///
+    /// The report will always be empty unless `options.lenient` is set, since otherwise we
+    /// abort on the first error.
+    pub fn with_options<P: AsRef<Path>>(
+        path_to_yyp: P,
+        options: &LoadOptions,
+    ) -> Result<(YypBoss, LoadReport), StartupError> {
+        let mut yyp_boss =
+            Self::without_resources_with_policy(path_to_yyp, &options.version_policy)?;
+        let mut report = LoadReport::default();
+
+        // read in every yy file, and its associated data, in parallel. We merge them in
+        // afterwards in yyp order, so errors are reported exactly as in a sequential load.
+        let root = yyp_boss.directory_manager.root_directory().to_owned();
+        let cache = options
+            .cache
+            .then(|| LoadCache::load(&yyp_boss.directory_manager));
+        let cache_ref = cache.as_ref();
+
+        let loaded: Vec<_> = yyp_boss
+            .yyp
+            .resources
+            .par_iter()
+            .map(|yyp_resource| {
+                let resource = parse_resource_listing(yyp_resource)?;
+                let assoc = options.associated_data.contains(&resource);
+
+                let output = match resource {
+                    Resource::Sprite => read_file::<Sprite>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::Script => read_file::<Script>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::Object => read_file::<Object>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::Note => read_file::<Note>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::Shader => read_file::<Shader>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::AnimationCurve => {
+                        read_file::<AnimationCurve>(&root, yyp_resource, assoc, cache_ref)
+                    }
+                    Resource::Room => read_file::<Room>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::Extension => {
+                        read_file::<Extension>(&root, yyp_resource, assoc, cache_ref)
+                    }
+                    Resource::Font => read_file::<Font>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::Path => read_file::<YyPath>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::Sequence => {
+                        read_file::<Sequence>(&root, yyp_resource, assoc, cache_ref)
+                    }
+                    Resource::Sound => read_file::<Sound>(&root, yyp_resource, assoc, cache_ref),
+                    Resource::TileSet => {
+                        read_file::<TileSet>(&root, yyp_resource, assoc, cache_ref)
+                    }
+                    Resource::Timeline => {
+                        read_file::<Timeline>(&root, yyp_resource, assoc, cache_ref)
+                    }
+                };
+
+                Ok((resource, output))
+            })
+            .collect();
+
+        // ...and merge them into the handlers and the vfs.
+        let mut cache_entries = vec![];
+        let yyp_resources = yyp_boss.yyp.resources.clone();
+        for (yyp_resource, loaded) in yyp_resources.into_iter().zip(loaded) {
+            let (resource, output) = match loaded {
+                Ok(v) => v,
+                Err(error) if options.lenient => {
+                    report.failures.push(LoadFailure {
+                        name: yyp_resource.id.name.clone(),
+                        yy_path: yyp_resource.id.path.clone(),
+                        resource: None,
+                        placeholder: false,
+                        error,
+                    });
+                    continue;
                 }
-                Resource::Room => load_in_file::<Room>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Extension => {
-                    load_in_file::<Extension>(yyp_resource, &mut yyp_boss, assoc)
+                Err(e) => return Err(e),
+            };
+
+            let output = output.and_then(|(merge, cache_entry)| {
+                if let Some(cache_entry) = cache_entry {
+                    cache_entries.push((yyp_resource.id.path.clone(), cache_entry));
                 }
-                Resource::Font => load_in_file::<Font>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Path => load_in_file::<YyPath>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Sequence => load_in_file::<Sequence>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Sound => load_in_file::<Sound>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::TileSet => load_in_file::<TileSet>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Timeline => load_in_file::<Timeline>(yyp_resource, &mut yyp_boss, assoc),
-            }?;
+
+                merge(&mut yyp_boss)
+            });
+
+            match output {
+                Ok(()) => {}
+                Err(error) if options.lenient => {
+                    // if only the associated data failed, the resource itself is fine
+                    let placeholder = matches!(error, StartupError::BadAssociatedData(..)) == false;
+                    if placeholder {
+                        yyp_boss.load_in_placeholder(&yyp_resource, resource, &error);
+                    }
+
+                    report.failures.push(LoadFailure {
+                        name: yyp_resource.id.name.clone(),
+                        yy_path: yyp_resource.id.path.clone(),
+                        resource: Some(resource),
+                        placeholder,
+                        error,
+                    });
+                }
+                Err(e) => return Err(e),
+            }
         }
 
-        return Ok(yyp_boss);
+        if let Some(cache) = cache {
+            let loaded = yyp_boss
+                .yyp
+                .resources
+                .iter()
+                .map(|v| v.id.path.as_path())
+                .collect();
+            cache.update(&yyp_boss.directory_manager, loaded, cache_entries);
+        }
 
-        fn load_in_file<T: YyResource>(
-            yyp_resource: YypResource,
-            yyp_boss: &mut YypBoss,
-            load_in_associated_data: bool,
-        ) -> Result<(), StartupError> {
-            let yy_file_path = yyp_boss
-                .directory_manager
-                .root_directory()
-                .join(yyp_resource.id.path);
-
-            let yy_file: T = utils::deserialize_json_tc(&yy_file_path, &TCU).map_err(|e| {
-                StartupError::BadYyFile {
-                    filepath: yy_file_path,
-                    error: e.to_string(),
-                }
-            })?;
+        return Ok((yyp_boss, report));
 
-            yyp_boss
-                .vfs
-                .load_in_file(&yy_file)
-                .map_err(|e| StartupError::BadResourceTree {
-                    name: yy_file.name().to_owned(),
-                    error: e.to_string(),
-                })?;
+        type Merge = Box<dyn FnOnce(&mut YypBoss) -> Result<(), StartupError> + Send>;
 
-            let name = yy_file.name().to_owned();
-            let root_path = yyp_boss.directory_manager.root_directory().to_owned();
-            let handler = T::get_handler_mut(yyp_boss);
-            handler.load_on_startup(yy_file);
+        /// Reads in the yy file, and its associated data if requested. This is run off the
+        /// main thread, so it returns a closure to merge the file into the YypBoss, and
+        /// the new entry to cache for the file, if we're caching and it was parsed.
+        fn read_file<T: YyResource>(
+            root: &Path,
+            yyp_resource: &YypResource,
+            load_in_associated_data: bool,
+            cache: Option<&LoadCache>,
+        ) -> Result<(Merge, Option<CacheEntry>), StartupError> {
+            let yy_file_path = root.join(&yyp_resource.id.path);
+
+            let output = match cache {
+                Some(cache) => cache.read(&yyp_resource.id.path, &yy_file_path, &TCU),
+                None => utils::deserialize_json_tc(&yy_file_path, &TCU).map(|yy| (yy, None)),
+            };
+            let (yy_file, cache_entry): (T, _) = output.map_err(|e| StartupError::BadYyFile {
+                filepath: yy_file_path,
+                error: e.to_string(),
+            })?;
 
-            if load_in_associated_data {
-                if let Err(e) = handler.load_resource_associated_data(&name, &root_path, &TCU) {
-                    return Err(StartupError::BadAssociatedData(name, e));
+            let associated_data = if load_in_associated_data {
+                let output = yy_file
+                    .deserialize_associated_data(&root.join(yy_file.relative_yy_directory()), &TCU)
+                    .map_err(|e| {
+                        StartupError::BadAssociatedData(yy_file.name().to_owned(), e.into())
+                    });
+
+                Some(output)
+            } else {
+                None
+            };
+
+            let merge = Box::new(move |yyp_boss: &mut YypBoss| {
+                yyp_boss
+                    .vfs
+                    .load_in_file(&yy_file)
+                    .map_err(|e| StartupError::BadResourceTree {
+                        name: yy_file.name().to_owned(),
+                        error: e.to_string(),
+                    })?;
+
+                let (associated_data, error) = match associated_data {
+                    Some(Ok(v)) => (Some(v), None),
+                    Some(Err(e)) => (None, Some(e)),
+                    None => (None, None),
+                };
+
+                T::get_handler_mut(yyp_boss).load_on_startup(yy_file, associated_data);
+
+                match error {
+                    Some(e) => Err(e),
+                    None => Ok(()),
                 }
-            }
+            });
 
-            Ok(())
+            Ok((merge, cache_entry))
         }
     }
 
+    /// Loads a resource which failed to load into the Vfs as a read-only placeholder.
+    fn load_in_placeholder(
+        &mut self,
+        yyp_resource: &YypResource,
+        resource: Resource,
+        error: &StartupError,
+    ) {
+        let yy_file_path = self.directory_manager.resource_file(&yyp_resource.id.path);
+
+        // we try to salvage *something* out of the file...
+        let raw: Option<serde_json::Value> = utils::deserialize_json_tc(yy_file_path, &TCU).ok();
+        let parent = raw
+            .as_ref()
+            .and_then(|v| v.get("parent"))
+            .and_then(|v| serde_json::from_value::<ViewPath>(v.clone()).ok())
+            .unwrap_or_else(|| self.project_metadata().root_file);
+
+        let parent_location =
+            self.vfs
+                .load_in_placeholder(&yyp_resource.id.name, resource, &parent.path);
+
+        self.placeholders.load_on_startup(Placeholder {
+            name: yyp_resource.id.name.clone(),
+            resource,
+            yy_path: yyp_resource.id.path.clone(),
+            parent: ViewPath {
+                name: parent.name,
+                path: parent_location,
+            },
+            error: error.to_string(),
+            raw,
+        });
+    }
+
     /// Loads the yyp *without* any resources. This is very fast and ideal for quick edits.
+    ///
+    /// This uses a strict [`VersionPolicy`]. To use a different policy, see
+    /// [`without_resources_with_policy`].
+    ///
+    /// [`without_resources_with_policy`]: #method.without_resources_with_policy
     pub fn without_resources<P: AsRef<Path>>(path_to_yyp: P) -> Result<YypBoss, StartupError> {
+        Self::without_resources_with_policy(path_to_yyp, &VersionPolicy::Strict)
+    }
+
+    /// Loads the yyp *without* any resources, checking its IDE version with the given policy.
+    pub fn without_resources_with_policy<P: AsRef<Path>>(
+        path_to_yyp: P,
+        version_policy: &VersionPolicy,
+    ) -> Result<YypBoss, StartupError> {
         let yyp: Yyp = utils::deserialize_json_tc(&path_to_yyp, &TCU).map_err(|e| match e {
             FileSerializationError::Serde(e) => StartupError::BadYypDeserialize(e),
             FileSerializationError::Io(error) => StartupError::BadYypPath {
@@ -133,28 +317,7 @@ impl YypBoss {
             },
         })?;
 
-        let (l_req, requirement) = Yyp::DEFAULT_VERSION
-            .split_once('.')
-            .map(|(l, r)| (l, semver::VersionReq::parse(r).unwrap()))
-            .unwrap();
-
-        let (l_actual, r_actual) = yyp
-            .meta_data
-            .ide_version
-            .split_once('.')
-            .map(|(l, r)| (l, semver::Version::parse(r).unwrap()))
-            .unwrap();
-
-        if l_req != l_actual {
-            return Err(StartupError::YypYearNotMatch(
-                l_req.to_string(),
-                l_actual.to_string(),
-            ));
-        }
-
-        if requirement.matches(&r_actual) == false {
-            return Err(StartupError::YypDoesNotMatch(requirement, r_actual));
-        }
+        check_ide_version(&yyp.meta_data.ide_version, version_policy)?;
 
         let directory_manager = DirectoryManager::new(path_to_yyp.as_ref())?;
 
@@ -175,22 +338,11 @@ impl YypBoss {
     /// but *only* do this if you know what you're doing, as the Vfs will no longer be accurate.
     pub fn quick_name(&mut self) -> Result<(), StartupError> {
         for yyp_resource in self.yyp.resources.clone() {
-            let path_as_str = yyp_resource.id.path.to_string_lossy();
-
-            let subpath = path_as_str
-                .split('/')
-                .next()
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
-
-            let resource = Resource::parse_subpath(subpath)
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
+            let resource = parse_resource_listing(&yyp_resource)?;
 
             self.vfs.resource_names.insert(
                 yyp_resource.id.name.clone(),
-                ResourceDescriptor {
-                    resource,
-                    parent_location: self.project_metadata().root_file.path,
-                },
+                ResourceDescriptor::new(resource, self.project_metadata().root_file.path),
             );
         }
 
@@ -210,34 +362,68 @@ impl YypBoss {
     }
 
     /// Serializes the YypBoss data to disk at the path of the Yyp.
+    ///
+    /// Dirty resources are written concurrently. If any of them fail, every failure is
+    /// returned as a [`SerializationErrors`]. The yyp is still written, and the resources
+    /// which failed stay dirty, so serializing again retries them.
     pub fn serialize(&mut self) -> AnyResult<()> {
         // serialize the vfs
         self.vfs
             .serialize(&mut self.yyp.folders, &mut self.yyp.resources);
 
-        // serialize all the tracked components
-        self.sprites.serialize(&self.directory_manager)?;
-        self.objects.serialize(&self.directory_manager)?;
-        self.scripts.serialize(&self.directory_manager)?;
-        self.notes.serialize(&self.directory_manager)?;
-        self.shaders.serialize(&self.directory_manager)?;
-        self.tilesets.serialize(&self.directory_manager)?;
-        self.sounds.serialize(&self.directory_manager)?;
-
-        // THESE DO NOT HAVE EXCELLENT TYPINGS YET.
-        self.animation_curves.serialize(&self.directory_manager)?;
-        self.extensions.serialize(&self.directory_manager)?;
-        self.fonts.serialize(&self.directory_manager)?;
-        self.paths.serialize(&self.directory_manager)?;
-        self.rooms.serialize(&self.directory_manager)?;
-        self.sequences.serialize(&self.directory_manager)?;
-        self.timelines.serialize(&self.directory_manager)?;
+        // serialize all the tracked components, concurrently. We collect every failure,
+        // rather than stopping at the first one.
+        let failures = std::sync::Mutex::new(vec![]);
+        let directory_manager = &self.directory_manager;
+        rayon::scope(|s| {
+            spawn_serialize(s, &mut self.sprites, directory_manager, &failures);
+            spawn_serialize(s, &mut self.objects, directory_manager, &failures);
+            spawn_serialize(s, &mut self.scripts, directory_manager, &failures);
+            spawn_serialize(s, &mut self.notes, directory_manager, &failures);
+            spawn_serialize(s, &mut self.shaders, directory_manager, &failures);
+            spawn_serialize(s, &mut self.tilesets, directory_manager, &failures);
+            spawn_serialize(s, &mut self.sounds, directory_manager, &failures);
+
+            // THESE DO NOT HAVE EXCELLENT TYPINGS YET.
+            spawn_serialize(s, &mut self.animation_curves, directory_manager, &failures);
+            spawn_serialize(s, &mut self.extensions, directory_manager, &failures);
+            spawn_serialize(s, &mut self.fonts, directory_manager, &failures);
+            spawn_serialize(s, &mut self.paths, directory_manager, &failures);
+            spawn_serialize(s, &mut self.rooms, directory_manager, &failures);
+            spawn_serialize(s, &mut self.sequences, directory_manager, &failures);
+            spawn_serialize(s, &mut self.timelines, directory_manager, &failures);
+        });
+
+        let mut failures = failures.into_inner().unwrap();
+        failures.extend(self.plain_gml.serialize(&self.directory_manager));
+        failures.extend(self.placeholders.serialize(&self.directory_manager));
+
+        // we write the yyp even if some resources failed, since the vfs has already changed
+        // it, and the other resources have already been written or removed.
 
         // Serialize Ourselves:
         let string = yy_typings::serialize_file(&self.yyp);
         fs::write(self.directory_manager.yyp(), string)?;
 
-        Ok(())
+        if failures.is_empty() == false {
+            failures.sort_by(|lhs, rhs| (lhs.resource, &lhs.name).cmp(&(rhs.resource, &rhs.name)));
+
+            return Err(SerializationErrors { failures }.into());
+        }
+
+        return Ok(());
+
+        fn spawn_serialize<'s, T: YyResource>(
+            scope: &rayon::Scope<'s>,
+            handler: &'s mut YyResourceHandler<T>,
+            directory_manager: &'s DirectoryManager,
+            failures: &'s std::sync::Mutex<Vec<SerializationFailure>>,
+        ) {
+            scope.spawn(move |_| {
+                let output = handler.serialize(directory_manager);
+                failures.lock().unwrap().extend(output);
+            });
+        }
     }
 
     pub fn version_string(&self) -> &str {
@@ -248,6 +434,7 @@ impl YypBoss {
         ProjectMetadata {
             name: self.yyp.common_data.name.clone(),
             ide_version: self.yyp.meta_data.ide_version.clone(),
+            detected_version: IdeVersion::parse(&self.yyp.meta_data.ide_version).ok(),
             yyp_version: self.yyp.common_data.resource_version,
             root_file: ViewPath {
                 name: self.yyp.common_data.name.clone(),
@@ -293,14 +480,24 @@ impl YypBoss {
         &mut self,
         name: &str,
     ) -> Result<(T, Option<T::AssociatedData>), ResourceManipulationError> {
+        if self.is_placeholder(name) {
+            return Err(ResourceManipulationError::ResourceIsPlaceholder);
+        }
+
         // remove the file from the VFS...
         self.vfs.remove_resource(name, T::RESOURCE)?;
 
         let path = self.directory_manager.root_directory().to_path_buf();
         let handler = T::get_handler_mut(self);
-        handler
+        let output = handler
             .remove(name, &path, &TCU)
-            .ok_or(ResourceManipulationError::InternalError)
+            .ok_or(ResourceManipulationError::InternalError)?;
+
+        // ...and any room or timeline code we've loaded for it
+        self.plain_gml
+            .remove_resource(T::RESOURCE, name, &output.0.relative_yy_directory());
+
+        Ok(output)
     }
 
     /// Adds a new resource, which must not already exist within the project.
@@ -324,19 +521,90 @@ impl YypBoss {
             return Ok(());
         }
 
+        if self.is_placeholder(name) {
+            return Err(ResourceManipulationError::ResourceIsPlaceholder);
+        }
+
         // rename the file in the VFS...
         self.vfs
             .rename_resource(name, T::RESOURCE, new_name.clone())?;
 
         let path = self.directory_manager.root_directory().to_path_buf();
         let handler = T::get_handler_mut(self);
+        let directory = handler
+            .get(name)
+            .map(|v| v.yy_resource.relative_yy_directory());
         handler
-            .rename(name, new_name, &path, &TCU)
+            .rename(name, new_name.clone(), &path, &TCU)
             .map_err(|_| ResourceManipulationError::InternalError)?;
 
+        // ...and any room or timeline code we've loaded for it
+        if let (Some(directory), Some(data)) = (directory, handler.get(&new_name)) {
+            let new_directory = data.yy_resource.relative_yy_directory();
+            self.plain_gml.rename_resource(
+                T::RESOURCE,
+                name,
+                &new_name,
+                &directory,
+                &new_directory,
+            );
+        }
+
         Ok(())
     }
 
+    /// Returns `true` if the resource of the given name failed to load, and is only held
+    /// as a read-only [`Placeholder`].
+    pub fn is_placeholder(&self, name: &str) -> bool {
+        self.vfs
+            .resource_names
+            .get(name)
+            .map(|v| v.placeholder)
+            .unwrap_or_default()
+    }
+
+    /// Removes a placeholder, which must already exist within the project. Its directory
+    /// will be removed on the next serialization.
+    pub fn remove_placeholder(
+        &mut self,
+        name: &str,
+    ) -> Result<Placeholder, ResourceManipulationError> {
+        let resource = self
+            .placeholders
+            .get(name)
+            .ok_or(ResourceManipulationError::BadGet)?
+            .resource;
+
+        self.vfs.remove_resource(name, resource)?;
+        self.placeholders
+            .remove(name)
+            .ok_or(ResourceManipulationError::InternalError)
+    }
+
+    /// Moves a placeholder within the Asset Tree. This rewrites the `parent` of its yy file,
+    /// and leaves the rest of the yy file exactly as it is.
+    pub fn move_placeholder(
+        &mut self,
+        name: &str,
+        new_parent: ViewPath,
+    ) -> Result<(), ResourceManipulationError> {
+        let placeholder = self
+            .placeholders
+            .get(name)
+            .ok_or(ResourceManipulationError::BadGet)?;
+
+        // check that we can rewrite it before we touch the vfs...
+        if placeholder.raw.as_ref().map(|v| v.is_object()) != Some(true) {
+            return Err(ResourceManipulationError::ResourceCannotBeManipulated);
+        }
+
+        self.vfs
+            .move_resource(name, placeholder.resource, &new_parent.path)
+            .map_err(ResourceManipulationError::FolderGraphError)?;
+
+        self.placeholders.edit_parent(name, new_parent)
+    }
+
     pub fn can_use_name(&self, name: &str) -> Result<(), ResourceManipulationError> {
         if self.vfs.resource_names.get(name).is_some() {
             return Err(ResourceManipulationError::NameCollision);
@@ -349,12 +617,30 @@ impl YypBoss {
         Ok(())
     }
 
+    /// Removes every file in an object's directory which isn't its yy file or the code of one
+    /// of its events, returning the paths which were removed. Folders are left alone.
+    ///
+    /// Serializing an object never removes files it doesn't know about, so this is how to
+    /// clean up after tools which leave files behind. Any pending serialization of the object
+    /// should be done first, or the code of its new events will be removed.
+    pub fn remove_unlisted_object_files(
+        &self,
+        object_name: &str,
+    ) -> Result<Vec<PathBuf>, YyResourceHandlerError> {
+        self.objects
+            .remove_unlisted_files(object_name, &self.directory_manager)
+    }
+
     /// Move a resource within the Asset Tree
     pub fn move_resource<T: YyResource>(
         &mut self,
         name: &str,
         new_parent: ViewPath,
     ) -> Result<(), ResourceManipulationError> {
+        if self.is_placeholder(name) {
+            return self.move_placeholder(name, new_parent);
+        }
+
         // cannot move them because we cannot reserialize them
         if T::RESOURCE.can_manipulate() == false {
             return Err(ResourceManipulationError::ResourceCannotBeManipulated);
@@ -423,6 +709,10 @@ impl YypBoss {
         new_parent: ViewPath,
         resource: Resource,
     ) -> Result<(), ResourceManipulationError> {
+        if self.is_placeholder(name) {
+            return self.move_placeholder(name, new_parent);
+        }
+
         match resource {
             Resource::Sprite => self.move_resource::<Sprite>(name, new_parent),
             Resource::Script => self.move_resource::<Script>(name, new_parent),
@@ -447,6 +737,11 @@ impl YypBoss {
         let deleted_resources = self.vfs.remove_non_empty_folder(folder)?;
 
         for (fsys, descriptor) in deleted_resources {
+            if descriptor.placeholder {
+                self.placeholders.remove(&fsys.name);
+                continue;
+            }
+
             match descriptor.resource {
                 Resource::Sprite => {
                     self.sprites
@@ -522,3 +817,847 @@ impl YypBoss {
         Ok(())
     }
 }
+
+// sprite tools
+impl YypBoss {
+    /// Cuts a sprite sheet into frames and makes a new sprite out of them, using the default
+    /// texture group. The sprite is not added to the project -- add it with [`add_resource`].
+    ///
+    /// [`add_resource`]: #method.add_resource
+    pub fn import_sprite_sheet(
+        &self,
+        sheet_path: &Path,
+        name: &str,
+        grid: &SheetGrid,
+        parent: ViewPath,
+    ) -> Result<(Sprite, SpriteImages), SpriteSheetError> {
+        let sheet = image::open(sheet_path)
+            .map_err(|e| SpriteSheetError::BadImage(e.to_string()))?
+            .to_rgba8();
+
+        let frames = grid.slice(&sheet)?;
+        sprite_from_frames(
+            name,
+            self.default_texture_path().unwrap_or_default(),
+            parent,
+            frames,
+        )
+    }
+
+    /// Makes a new sprite out of a strip named with GameMaker's strip convention, such as
+    /// `spr_walk_strip8.png`, which becomes the 8 frame sprite `spr_walk`. The sprite is not
+    /// added to the project -- add it with [`add_resource`].
+    ///
+    /// [`add_resource`]: #method.add_resource
+    pub fn import_sprite_strip(
+        &self,
+        strip_path: &Path,
+        parent: ViewPath,
+    ) -> Result<(Sprite, SpriteImages), SpriteSheetError> {
+        let stem = strip_path
+            .file_stem()
+            .map(|v| v.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let (name, frames) =
+            parse_strip_name(&stem).ok_or_else(|| SpriteSheetError::NotAStrip(stem.clone()))?;
+
+        let sheet = image::open(strip_path)
+            .map_err(|e| SpriteSheetError::BadImage(e.to_string()))?
+            .to_rgba8();
+
+        let grid = SheetGrid::strip(frames, sheet.width(), sheet.height())?;
+        sprite_from_frames(
+            name,
+            self.default_texture_path().unwrap_or_default(),
+            parent,
+            grid.slice(&sheet)?,
+        )
+    }
+
+    /// Makes sprites out of an Aseprite file, using the default texture group. Depending on
+    /// `tags`, this is either a single sprite named `name`, or a sprite for each of the file's
+    /// tags. The sprites are not added to the project -- add them with [`add_resource`].
+    ///
+    /// [`add_resource`]: #method.add_resource
+    pub fn import_aseprite(
+        &self,
+        aseprite_path: &Path,
+        name: &str,
+        tags: AsepriteTags,
+        parent: ViewPath,
+    ) -> Result<Vec<(Sprite, SpriteImages)>, AsepriteError> {
+        AsepriteFile::open(aseprite_path)?.to_sprites(
+            name,
+            self.default_texture_path().unwrap_or_default(),
+            parent,
+            tags,
+        )
+    }
+
+    /// Exports a sprite's frames as a sprite sheet PNG at `png_path`, with a JSON
+    /// [`SheetAtlas`] written next to it with a `json` extension. The sprite's images
+    /// must be loaded.
+    pub fn export_sprite_sheet(
+        &self,
+        sprite_name: &str,
+        png_path: &Path,
+    ) -> Result<SheetAtlas, SpriteExportError> {
+        let (sprite, images) = self.sprite_for_export(sprite_name)?;
+        let (sheet, atlas) = sprite_sheet(sprite, images)?;
+
+        sheet
+            .save_with_format(png_path, image::ImageFormat::Png)
+            .map_err(|e| SpriteExportError::Encoding(e.to_string()))?;
+        let atlas_json = serde_json::to_string_pretty(&atlas)
+            .map_err(|e| SpriteExportError::Encoding(e.to_string()))?;
+        fs::write(png_path.with_extension("json"), atlas_json)?;
+
+        Ok(atlas)
+    }
+
+    /// Exports a sprite as a looping animated GIF at `gif_path`. Sprites which play back in
+    /// frames per game frame are timed against `game_speed`, in game frames per second.
+    /// The sprite's images must be loaded.
+    pub fn export_sprite_gif(
+        &self,
+        sprite_name: &str,
+        gif_path: &Path,
+        game_speed: f64,
+    ) -> Result<(), SpriteExportError> {
+        let (sprite, images) = self.sprite_for_export(sprite_name)?;
+        let file = std::io::BufWriter::new(fs::File::create(gif_path)?);
+
+        sprite_gif(sprite, images, game_speed, file)
+    }
+
+    fn sprite_for_export(
+        &self,
+        sprite_name: &str,
+    ) -> Result<(&Sprite, &SpriteImages), SpriteExportError> {
+        let data = self
+            .sprites
+            .get(sprite_name)
+            .ok_or_else(|| SpriteExportError::NoSprite(sprite_name.to_string()))?;
+        let images = data
+            .associated_data
+            .as_ref()
+            .ok_or(SpriteExportError::AssociatedDataNotLoaded)?;
+
+        Ok((&data.yy_resource, images))
+    }
+
+    /// The maximum texture page size set in the project's Windows options, if it can be read.
+    pub fn texture_page_size(&self) -> Option<(u32, u32)> {
+        let path = self
+            .directory_manager
+            .resource_file(Path::new("options/windows/options_windows.yy"));
+        let options: serde_json::Value = utils::deserialize_json_tc(path, self.tcu()).ok()?;
+
+        let (width, height) = options
+            .get("option_windows_texture_page")?
+            .as_str()?
+            .split_once('x')?;
+
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    }
+
+    /// Simulates packing every sprite in a texture group onto texture pages, using the group's
+    /// border and autocrop settings. The sprites' images are loaded if they aren't already.
+    ///
+    /// If no page size is given, the project's [`texture_page_size`] is used, or GameMaker's
+    /// default if it can't be read. If an output directory is given, each simulated page is
+    /// written to it as `<texture_group>_<page>.png`.
+    ///
+    /// [`texture_page_size`]: #method.texture_page_size
+    pub fn pack_texture_group(
+        &mut self,
+        texture_group: &str,
+        page_size: Option<(u32, u32)>,
+        output_directory: Option<&Path>,
+    ) -> Result<PackingReport, TexturePackingError> {
+        let group = self
+            .yyp
+            .texture_groups
+            .iter()
+            .find(|v| v.common_data.name == texture_group)
+            .ok_or_else(|| TexturePackingError::NoTextureGroup(texture_group.to_string()))?;
+        let page_size = page_size
+            .or_else(|| self.texture_page_size())
+            .unwrap_or(PackingSettings::DEFAULT_PAGE_SIZE);
+        let settings = PackingSettings::from_texture_group(group, page_size);
+
+        // sorted, so the pages are the same every time
+        let mut names: Vec<String> = self
+            .sprites
+            .resources()
+            .values()
+            .filter(|v| v.yy_resource.texture_group_id.name == texture_group)
+            .map(|v| v.yy_resource.common_data.name.clone())
+            .collect();
+        names.sort();
+        for name in names.iter() {
+            self.ensure_associated_data_is_loaded::<Sprite>(name, false)
+                .map_err(|e| TexturePackingError::CouldNotLoadSprite {
+                    sprite: name.to_string(),
+                    error: e.to_string(),
+                })?;
+        }
+
+        let sprites: Vec<(&Sprite, &SpriteImages)> = names
+            .iter()
+            .filter_map(|name| {
+                let data = self.sprites.get(name)?;
+                Some((&data.yy_resource, data.associated_data.as_ref()?))
+            })
+            .collect();
+        let report = pack_sprites(&sprites, settings);
+
+        if let Some(directory) = output_directory {
+            for (i, page) in report.render_pages(&sprites).into_iter().enumerate() {
+                page.save_with_format(
+                    directory.join(format!("{}_{}.png", texture_group, i)),
+                    image::ImageFormat::Png,
+                )
+                .map_err(|e| TexturePackingError::CouldNotWritePage(e.to_string()))?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Finds identical and similar frames, and identical sprites, across every sprite whose
+    /// images are loaded. Sprites which aren't loaded are listed in the report instead of
+    /// being loaded, so load them first to check the whole project.
+    ///
+    /// See [`find_duplicates`] for what `max_distance` means.
+    ///
+    /// [`find_duplicates`]: crate::find_duplicates
+    pub fn find_duplicate_frames(&self, max_distance: u32) -> DuplicateReport {
+        let mut sprites = vec![];
+        let mut unloaded = vec![];
+        for data in self.sprites.resources().values() {
+            match &data.associated_data {
+                Some(images) => sprites.push((&data.yy_resource, images)),
+                None => unloaded.push(data.yy_resource.common_data.name.clone()),
+            }
+        }
+        // the handler's order isn't stable, so we sort to keep reports the same between runs
+        sprites.sort_by(|a, b| a.0.common_data.name.cmp(&b.0.common_data.name));
+        unloaded.sort();
+
+        let mut report = find_duplicates(&sprites, max_distance);
+        report.unloaded = unloaded;
+
+        report
+    }
+}
+
+// gml tools
+impl YypBoss {
+    /// Loads the code of every script, object, room and timeline which isn't loaded yet.
+    fn ensure_gml_is_loaded(&mut self) -> Result<(), YyResourceHandlerError> {
+        let scripts: Vec<String> = self.scripts.resources().keys().cloned().collect();
+        for name in scripts {
+            self.ensure_associated_data_is_loaded::<Script>(&name, false)?;
+        }
+
+        let objects: Vec<String> = self.objects.resources().keys().cloned().collect();
+        for name in objects {
+            self.ensure_associated_data_is_loaded::<Object>(&name, false)?;
+        }
+
+        for data in self.rooms.resources().values() {
+            let room = &data.yy_resource;
+            self.plain_gml.load_room(
+                &self.directory_manager,
+                room.name(),
+                &room.relative_yy_directory(),
+            )?;
+        }
+
+        for data in self.timelines.resources().values() {
+            let timeline = &data.yy_resource;
+            self.plain_gml.load_timeline(
+                &self.directory_manager,
+                timeline.name(),
+                &timeline.relative_yy_directory(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Scans the project's GML for functions, macros, enums and globalvars. Its code is
+    /// loaded if it isn't already.
+    pub fn gml_symbols(&mut self) -> Result<SymbolTable, YyResourceHandlerError> {
+        self.ensure_gml_is_loaded()?;
+
+        let sources = self
+            .gml_files()
+            .into_iter()
+            .map(|(source, _, gml)| (source, gml));
+
+        Ok(SymbolTable::new(sources))
+    }
+
+    /// Finds every use of a name in the project's GML, sorted by file and position. See
+    /// [`find_identifier`] for what counts as a use. The GML is loaded if it isn't already.
+    ///
+    /// [`find_identifier`]: crate::find_identifier
+    pub fn find_gml_usages(&mut self, name: &str) -> Result<Vec<GmlUsage>, YyResourceHandlerError> {
+        self.ensure_gml_is_loaded()?;
+
+        Ok(self.gml_usages(name))
+    }
+
+    /// Renames a resource, like [`rename_resource`], and then renames every use of it in the
+    /// project's GML. Returns the uses which were renamed, where they were before the rename.
+    ///
+    /// [`rename_resource`]: #method.rename_resource
+    pub fn rename_resource_and_usages<T: YyResource>(
+        &mut self,
+        name: &str,
+        new_name: String,
+    ) -> Result<Vec<GmlUsage>, ResourceManipulationError> {
+        // we load first, since renamed resources can't be loaded until they're serialized
+        self.ensure_gml_is_loaded()
+            .map_err(|e| ResourceManipulationError::CouldNotLoadGml(e.to_string()))?;
+        let usages = self.gml_usages(name);
+
+        self.rename_resource::<T>(name, new_name.clone())?;
+        self.rewrite_gml(|gml| rename_identifier(gml, name, &new_name));
+
+        Ok(usages)
+    }
+
+    /// Finds every match of a pattern in the project's GML, sorted by file and position. The
+    /// GML is loaded if it isn't already.
+    pub fn search_gml(&mut self, pattern: &Regex) -> Result<Vec<GmlMatch>, YyResourceHandlerError> {
+        self.ensure_gml_is_loaded()?;
+
+        Ok(self.gml_matches(pattern))
+    }
+
+    /// Replaces every match of a pattern in the project's GML, and marks every changed file
+    /// for serialization. The replacement can use the pattern's capture groups, such as `$1`,
+    /// like [`Regex::replace_all`]. Returns the matches, where they were before the
+    /// replacement.
+    pub fn replace_gml(
+        &mut self,
+        pattern: &Regex,
+        replacement: &str,
+    ) -> Result<Vec<GmlMatch>, YyResourceHandlerError> {
+        self.ensure_gml_is_loaded()?;
+        let matches = self.gml_matches(pattern);
+
+        self.rewrite_gml(|gml| pattern.replace_all(gml, replacement).into_owned());
+
+        Ok(matches)
+    }
+
+    /// The project's GML: every loaded script, object event, room creation code and timeline
+    /// moment, with where its code is relative to the project's root directory.
+    fn gml_files(&self) -> Vec<(GmlSource, PathBuf, &str)> {
+        let mut output = vec![];
+
+        for data in self.scripts.resources().values() {
+            let Some(gml) = data.associated_data.as_ref() else {
+                continue;
+            };
+            let script = &data.yy_resource.common_data.name;
+            let path = data
+                .yy_resource
+                .relative_yy_directory()
+                .join(format!("{}.gml", script));
+
+            output.push((
+                GmlSource::Script {
+                    name: script.clone(),
+                },
+                path,
+                gml.as_str(),
+            ));
+        }
+
+        for data in self.objects.resources().values() {
+            let Some(events) = data.associated_data.as_ref() else {
+                continue;
+            };
+            let object = &data.yy_resource.common_data.name;
+
+            for (event, gml) in events {
+                let path = data
+                    .yy_resource
+                    .relative_yy_directory()
+                    .join(format!("{}.gml", event.filename()));
+
+                output.push((
+                    GmlSource::ObjectEvent {
+                        object: object.clone(),
+                        event: event.filename(),
+                    },
+                    path,
+                    gml.as_str(),
+                ));
+            }
+        }
+
+        for (path, file) in self.plain_gml.files() {
+            output.push((file.source.clone(), path.clone(), file.gml.as_str()));
+        }
+
+        output
+    }
+
+    fn gml_usages(&self, name: &str) -> Vec<GmlUsage> {
+        let mut output: Vec<GmlUsage> = self
+            .gml_files()
+            .into_iter()
+            .flat_map(|(source, path, gml)| {
+                find_identifier(gml, name)
+                    .into_iter()
+                    .map(move |position| GmlUsage {
+                        source: source.clone(),
+                        path: path.clone(),
+                        position,
+                    })
+            })
+            .collect();
+        output.sort_by(|a, b| (&a.path, a.position).cmp(&(&b.path, b.position)));
+
+        output
+    }
+
+    fn gml_matches(&self, pattern: &Regex) -> Vec<GmlMatch> {
+        let mut output: Vec<GmlMatch> = self
+            .gml_files()
+            .into_iter()
+            .flat_map(|(source, path, gml)| {
+                find_pattern(gml, pattern)
+                    .into_iter()
+                    .map(move |(position, text)| GmlMatch {
+                        source: source.clone(),
+                        path: path.clone(),
+                        position,
+                        text: text.to_string(),
+                    })
+            })
+            .collect();
+        output.sort_by(|a, b| (&a.path, a.position).cmp(&(&b.path, b.position)));
+
+        output
+    }
+
+    /// Rewrites the project's loaded GML, marking the files which changed for serialization.
+    fn rewrite_gml(&mut self, rewrite: impl Fn(&str) -> String) {
+        let scripts: Vec<String> = self.scripts.resources().keys().cloned().collect();
+        for script in scripts {
+            // safety: we only keep the edit if we mark the script for serialization
+            let data = unsafe { self.scripts.get_mut(&script) }.unwrap();
+            let Some(gml) = data.associated_data.as_mut() else {
+                continue;
+            };
+
+            let rewritten = rewrite(gml);
+            if rewritten != *gml {
+                *gml = rewritten;
+                self.scripts.mark_for_serialization(&script).unwrap();
+            }
+        }
+
+        let objects: Vec<String> = self.objects.resources().keys().cloned().collect();
+        for object in objects {
+            // safety: we only keep the edit if we mark the object for serialization
+            let data = unsafe { self.objects.get_mut(&object) }.unwrap();
+            let Some(events) = data.associated_data.as_mut() else {
+                continue;
+            };
+
+            let mut changed = false;
+            for gml in events.values_mut() {
+                let rewritten = rewrite(gml);
+                if rewritten != *gml {
+                    *gml = rewritten;
+                    changed = true;
+                }
+            }
+            if changed {
+                self.objects.mark_for_serialization(&object).unwrap();
+            }
+        }
+
+        self.plain_gml.rewrite(rewrite);
+    }
+}
+
+/// Finds the kind of resource from its listing in the yyp.
+fn parse_resource_listing(yyp_resource: &YypResource) -> Result<Resource, StartupError> {
+    let path_as_str = yyp_resource.id.path.to_string_lossy();
+
+    let subpath = path_as_str
+        .split('/')
+        .next()
+        .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
+
+    Resource::parse_subpath(subpath)
+        .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))
+}
+
+/// Checks the yyp's IDE version against the version this crate supports, using the given policy.
+fn check_ide_version(ide_version: &str, policy: &VersionPolicy) -> Result<(), StartupError> {
+    let found = IdeVersion::parse(ide_version).map_err(StartupError::BadYypVersion);
+
+    match policy {
+        VersionPolicy::Strict => {
+            let found = found?;
+            let supported = IdeVersion::supported();
+
+            if supported.year != found.year {
+                return Err(StartupError::YypYearNotMatch(
+                    supported.year.to_string(),
+                    found.year.to_string(),
+                ));
+            }
+
+            let requirement = supported.requirement();
+            if requirement.matches(&found.version) == false {
+                return Err(StartupError::YypDoesNotMatch(requirement, found.version));
+            }
+        }
+        VersionPolicy::Warn => match found {
+            Ok(found) => {
+                if found != IdeVersion::supported() {
+                    log::warn!(
+                        "yyp was made with ide version {}, but only {} is supported",
+                        found,
+                        IdeVersion::supported()
+                    );
+                }
+            }
+            Err(e) => log::warn!("{}", e),
+        },
+        VersionPolicy::AcceptRange { min, max } => {
+            let found = found?;
+
+            if found < *min || found > *max {
+                return Err(StartupError::YypVersionOutOfRange {
+                    min: min.to_string(),
+                    max: max.to_string(),
+                    found: found.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpriteImageBuffer;
+    use std::collections::HashMap;
+    use yy_typings::{CommonData, FilesystemPath, FrameId, LayerId, SpriteLayer};
+
+    /// Makes a project in a new temporary directory, with notes in a `Notes` folder.
+    fn new_project(name: &str, notes: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("yy-boss-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let yyp_path = root.join("project.yyp");
+        fs::write(&yyp_path, yy_typings::serialize_file(&Yyp::default())).unwrap();
+
+        let mut yyp_boss = YypBoss::new(&yyp_path, &[]).unwrap();
+        let folder = yyp_boss
+            .vfs
+            .new_folder_end(&yyp_boss.vfs.root_file_viewpath(), "Notes")
+            .unwrap();
+        for note in notes {
+            let note = Note {
+                common_data: CommonData::new(note.to_string()),
+                parent: folder.clone(),
+            };
+            yyp_boss.add_resource(note, String::new()).unwrap();
+        }
+        yyp_boss.serialize().unwrap();
+
+        yyp_path
+    }
+
+    #[test]
+    fn lenient_load() {
+        let yyp_path = new_project("lenient", &["nt_good", "nt_broken"]);
+        let root = yyp_path.parent().unwrap();
+
+        // a yy file which is json, but not a note, since it has no parent
+        let broken_path = root.join("notes/nt_broken/nt_broken.yy");
+        let mut broken: serde_json::Value = utils::deserialize_json_tc(&broken_path, &TCU).unwrap();
+        broken.as_object_mut().unwrap().remove("parent");
+        fs::write(&broken_path, serde_json::to_string(&broken).unwrap()).unwrap();
+
+        assert!(YypBoss::new(&yyp_path, &[]).is_err());
+
+        let options = LoadOptions {
+            lenient: true,
+            ..LoadOptions::default()
+        };
+        let (mut yyp_boss, report) = YypBoss::with_options(&yyp_path, &options).unwrap();
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].name, "nt_broken");
+        assert_eq!(report.failures[0].resource, Some(Resource::Note));
+        assert!(report.failures[0].placeholder);
+        assert!(yyp_boss.notes.get("nt_good").is_some());
+        assert!(yyp_boss.is_placeholder("nt_broken"));
+
+        let folder = yyp_boss
+            .vfs
+            .new_folder_end(&yyp_boss.vfs.root_file_viewpath(), "Broken")
+            .unwrap();
+        yyp_boss
+            .move_placeholder("nt_broken", folder.clone())
+            .unwrap();
+
+        // a placeholder which can't be written is reported, and retried on the next serialize
+        fs::remove_file(&broken_path).unwrap();
+        fs::create_dir(&broken_path).unwrap();
+        let error = yyp_boss.serialize().unwrap_err();
+        let failures = &error
+            .downcast_ref::<SerializationErrors>()
+            .unwrap()
+            .failures;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "nt_broken");
+        fs::remove_dir(&broken_path).unwrap();
+        yyp_boss.serialize().unwrap();
+
+        // moving it wrote the parent it was missing, so now it loads as a note
+        let (yyp_boss, report) = YypBoss::with_options(&yyp_path, &options).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(
+            yyp_boss.notes.get("nt_broken").unwrap().yy_resource.parent,
+            folder
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn serialize_failures() {
+        let yyp_path = new_project("serialize", &[]);
+        let root = yyp_path.parent().unwrap();
+        let mut yyp_boss = YypBoss::new(&yyp_path, &[]).unwrap();
+
+        // a file where a note's directory should be stops it from being written
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(root.join("notes/nt_blocked"), "").unwrap();
+
+        for name in ["nt_fine", "nt_blocked"] {
+            let note = Note {
+                common_data: CommonData::new(name.to_string()),
+                parent: ViewPath {
+                    name: "Notes".to_string(),
+                    path: ViewPathLocation("folders/Notes.yy".to_string()),
+                },
+            };
+            yyp_boss.add_resource(note, String::new()).unwrap();
+        }
+
+        let error = yyp_boss.serialize().unwrap_err();
+        let failures = &error
+            .downcast_ref::<SerializationErrors>()
+            .unwrap()
+            .failures;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "nt_blocked");
+
+        // the yyp is written anyway, along with every resource which didn't fail
+        let written = YypBoss::without_resources(&yyp_path).unwrap();
+        let names: Vec<&str> = written
+            .yyp()
+            .resources
+            .iter()
+            .map(|v| v.id.name.as_str())
+            .collect();
+        assert!(names.contains(&"nt_fine") && names.contains(&"nt_blocked"));
+        assert!(root.join("notes/nt_fine/nt_fine.yy").exists());
+
+        fs::remove_file(root.join("notes/nt_blocked")).unwrap();
+        yyp_boss.serialize().unwrap();
+        assert!(root.join("notes/nt_blocked/nt_blocked.yy").exists());
+        assert!(YypBoss::new(&yyp_path, &[Resource::Note]).is_ok());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn edit_cleanup() {
+        let yyp_path = new_project("edit-cleanup", &[]);
+        let root = yyp_path.parent().unwrap();
+        let mut yyp_boss = YypBoss::new(&yyp_path, &[]).unwrap();
+
+        let frames = vec![SpriteImageBuffer::new(2, 2); 2];
+        let parent = yyp_boss
+            .vfs
+            .new_folder_end(&yyp_boss.vfs.root_file_viewpath(), "Sprites")
+            .unwrap();
+        let (sprite, images) =
+            sprite_from_frames("spr_test", TexturePath::default(), parent, frames).unwrap();
+        let frame_ids: Vec<FrameId> = sprite.frames.iter().map(|v| v.name).collect();
+        let layer = SpriteLayer {
+            display_name: "top".to_string(),
+            common_data: CommonData::new(LayerId::new()),
+            ..sprite.layers[0].clone()
+        };
+        let layer_id = layer.common_data.name;
+        yyp_boss.add_resource(sprite, images).unwrap();
+        yyp_boss
+            .sprites
+            .add_layer("spr_test", 0, layer, HashMap::new())
+            .unwrap();
+        yyp_boss.serialize().unwrap();
+
+        let sprite_dir = root.join("sprites/spr_test");
+        let frame_dir = |frame: FrameId| sprite_dir.join("layers").join(frame.inner().to_string());
+        let layer_file = format!("{}.png", layer_id.inner());
+        assert!(frame_dir(frame_ids[1]).join(&layer_file).exists());
+
+        // removing a layer only removes that layer's images...
+        yyp_boss.sprites.remove_layer("spr_test", layer_id).unwrap();
+        yyp_boss.serialize().unwrap();
+        assert!(frame_dir(frame_ids[1]).join(&layer_file).exists() == false);
+        assert_eq!(fs::read_dir(frame_dir(frame_ids[1])).unwrap().count(), 1);
+
+        // ...and removing a frame removes its composite image and its layer folder
+        yyp_boss
+            .sprites
+            .remove_frame("spr_test", frame_ids[0])
+            .unwrap();
+        yyp_boss.serialize().unwrap();
+        let composite = |frame: FrameId| sprite_dir.join(format!("{}.png", frame.inner()));
+        assert!(composite(frame_ids[0]).exists() == false);
+        assert!(frame_dir(frame_ids[0]).exists() == false);
+        assert!(composite(frame_ids[1]).exists());
+        assert!(frame_dir(frame_ids[1]).exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn plain_gml() {
+        let yyp_path = new_project("plain-gml", &[]);
+        let root = yyp_path.parent().unwrap();
+        let parent = ViewPath {
+            name: "Notes".to_string(),
+            path: ViewPathLocation("folders/Notes.yy".to_string()),
+        };
+
+        // rooms and timelines can't be added through the boss, so we write them ourselves
+        let mut yyp: Yyp = utils::deserialize_json_tc(&yyp_path, &TCU).unwrap();
+        let room = Room {
+            parent: parent.clone(),
+            name: "rm_start".to_string(),
+            ..Room::default()
+        };
+        let timeline = Timeline {
+            parent,
+            name: "tl_intro".to_string(),
+            ..Timeline::default()
+        };
+        for (base, name, yy) in [
+            ("rooms", "rm_start", yy_typings::serialize_file(&room)),
+            (
+                "timelines",
+                "tl_intro",
+                yy_typings::serialize_file(&timeline),
+            ),
+        ] {
+            let id = FilesystemPath::new(base, name);
+            fs::create_dir_all(root.join(&id.path).parent().unwrap()).unwrap();
+            fs::write(root.join(&id.path), yy).unwrap();
+            yyp.resources.push(YypResource { id });
+        }
+        fs::write(&yyp_path, yy_typings::serialize_file(&yyp)).unwrap();
+
+        let files = [
+            ("rooms/rm_start/RoomCreationCode.gml", "hp = 10;"),
+            ("rooms/rm_start/InstanceCreationCode_inst_1.gml", "hp = 5;"),
+            ("rooms/rm_start/notes.gml", "hp = 0;"),
+            ("timelines/tl_intro/moment_30.gml", "hp += 1;"),
+        ];
+        for (path, gml) in files {
+            fs::write(root.join(path), gml).unwrap();
+        }
+
+        let mut yyp_boss = YypBoss::new(&yyp_path, &[]).unwrap();
+        let pattern = Regex::new(r"\bhp\b").unwrap();
+        let matches = yyp_boss.replace_gml(&pattern, "health").unwrap();
+        let sources: Vec<GmlSource> = matches.into_iter().map(|v| v.source).collect();
+        assert_eq!(
+            sources,
+            vec![
+                GmlSource::RoomCreationCode {
+                    room: "rm_start".to_string(),
+                    file: "InstanceCreationCode_inst_1".to_string(),
+                },
+                GmlSource::RoomCreationCode {
+                    room: "rm_start".to_string(),
+                    file: "RoomCreationCode".to_string(),
+                },
+                GmlSource::TimelineMoment {
+                    timeline: "tl_intro".to_string(),
+                    moment: "moment_30".to_string(),
+                },
+            ]
+        );
+
+        yyp_boss.serialize().unwrap();
+        let read = |path: &str| fs::read_to_string(root.join(path)).unwrap();
+        assert_eq!(read(files[0].0), "health = 10;");
+        assert_eq!(read(files[1].0), "health = 5;");
+        assert_eq!(read(files[2].0), "hp = 0;");
+        assert_eq!(read(files[3].0), "health += 1;");
+
+        // a removed room's edited code is neither searched nor written...
+        let pattern = Regex::new(r"\bhealth\b").unwrap();
+        yyp_boss.replace_gml(&pattern, "hp").unwrap();
+        yyp_boss.remove_resource::<Room>("rm_start").unwrap();
+        let matches = yyp_boss.search_gml(&pattern).unwrap();
+        assert!(matches
+            .iter()
+            .all(|v| v.source.resource().0 != Resource::Room));
+
+        yyp_boss.serialize().unwrap();
+        assert!(root.join("rooms/rm_start").exists() == false);
+        assert_eq!(read(files[3].0), "hp += 1;");
+
+        // ...and a renamed timeline's code is searched once, under its new name. Rooms and
+        // timelines can't be renamed through the boss yet, so we do what `rename_resource` would.
+        yyp_boss
+            .timelines
+            .rename("tl_intro", "tl_outro".to_string(), root, &TCU)
+            .unwrap();
+        yyp_boss.plain_gml.rename_resource(
+            Resource::Timeline,
+            "tl_intro",
+            "tl_outro",
+            Path::new("timelines/tl_intro"),
+            Path::new("timelines/tl_outro"),
+        );
+        let sources: Vec<GmlSource> = yyp_boss
+            .search_gml(&Regex::new(r"\bhp\b").unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|v| v.source)
+            .collect();
+        assert_eq!(
+            sources,
+            vec![GmlSource::TimelineMoment {
+                timeline: "tl_outro".to_string(),
+                moment: "moment_30".to_string(),
+            }]
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}